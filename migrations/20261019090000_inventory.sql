@@ -0,0 +1,33 @@
+-- Per-product stock levels and checkout reservations. A NULL
+-- `stock_quantity` means stock is not tracked and the product never sells
+-- out, which is what every existing product starts as.
+
+ALTER TABLE products
+    ADD COLUMN stock_quantity INTEGER CHECK (stock_quantity >= 0),
+    ADD COLUMN reserved_quantity INTEGER NOT NULL DEFAULT 0 CHECK (reserved_quantity >= 0),
+    ADD CONSTRAINT products_reserved_within_stock CHECK (reserved_quantity <= stock_quantity);
+
+CREATE TABLE IF NOT EXISTS order_items (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    product_id UUID NOT NULL REFERENCES products(id),
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    price BIGINT NOT NULL
+);
+
+CREATE TABLE stock_reservations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    product_id UUID NOT NULL REFERENCES products(id),
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'committed', 'released')),
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (order_id, product_id)
+);
+
+CREATE INDEX stock_reservations_active_expiry_idx
+    ON stock_reservations (expires_at)
+    WHERE status = 'active';
+
+CREATE INDEX orders_payment_id_idx ON orders (payment_id);
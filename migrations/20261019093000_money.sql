@@ -0,0 +1,9 @@
+-- Store every amount as integer minor units next to its currency.
+
+ALTER TABLE products
+    ALTER COLUMN price TYPE BIGINT USING ROUND(price * 100)::BIGINT,
+    ADD COLUMN currency TEXT NOT NULL DEFAULT 'inr';
+
+ALTER TABLE order_items
+    ADD COLUMN currency TEXT NOT NULL DEFAULT 'inr';
+
@@ -0,0 +1,19 @@
+-- Exchange rates, per-currency price lists and customer currency preference.
+
+-- How many units of `currency` buy one unit of the store base currency
+-- (BASE_CURRENCY). The base currency itself needs no row.
+CREATE TABLE exchange_rates (
+    currency TEXT PRIMARY KEY,
+    units_per_base NUMERIC(20, 10) NOT NULL CHECK (units_per_base > 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Explicit prices that win over FX conversion for a given currency.
+CREATE TABLE product_prices (
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    currency TEXT NOT NULL,
+    amount BIGINT NOT NULL CHECK (amount >= 0),
+    PRIMARY KEY (product_id, currency)
+);
+
+ALTER TABLE users ADD COLUMN preferred_currency TEXT;
@@ -0,0 +1,3 @@
+-- Stable supplier identifier used by catalogue import/export.
+
+ALTER TABLE products ADD COLUMN sku TEXT UNIQUE;
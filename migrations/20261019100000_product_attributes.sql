@@ -0,0 +1,13 @@
+-- Typed product attributes stored as JSONB, with facet-able definitions.
+
+CREATE TABLE attribute_definitions (
+    key TEXT PRIMARY KEY CHECK (key ~ '^[a-z0-9_]+$'),
+    label TEXT NOT NULL,
+    data_type TEXT NOT NULL CHECK (data_type IN ('text', 'number', 'boolean')),
+    facetable BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+ALTER TABLE products ADD COLUMN attributes JSONB NOT NULL DEFAULT '{}';
+
+-- Serves the `attributes @> ...` equality filters of product listings.
+CREATE INDEX products_attributes_idx ON products USING GIN (attributes jsonb_path_ops);
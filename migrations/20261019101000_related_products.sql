@@ -0,0 +1,27 @@
+-- Categories and co-purchase statistics for product recommendations.
+
+CREATE TABLE categories (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    slug TEXT NOT NULL UNIQUE CHECK (slug ~ '^[a-z0-9-]+$'),
+    name TEXT NOT NULL
+);
+
+ALTER TABLE products ADD COLUMN category_id UUID REFERENCES categories(id) ON DELETE SET NULL;
+
+CREATE INDEX products_category_idx ON products (category_id);
+
+-- How many paid orders contained both products. Refreshed periodically by the
+-- server rather than on every order.
+CREATE MATERIALIZED VIEW product_co_purchases AS
+SELECT a.product_id,
+       b.product_id AS related_product_id,
+       COUNT(DISTINCT a.order_id) AS order_count
+FROM order_items a
+JOIN order_items b ON b.order_id = a.order_id AND b.product_id <> a.product_id
+JOIN orders o ON o.id = a.order_id
+WHERE o.status = 'paid'
+GROUP BY a.product_id, b.product_id;
+
+-- Required for REFRESH ... CONCURRENTLY.
+CREATE UNIQUE INDEX product_co_purchases_pair_idx
+    ON product_co_purchases (product_id, related_product_id);
@@ -0,0 +1,16 @@
+-- Trigram indexes and sales popularity for search type-ahead.
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX products_name_trgm_idx ON products USING GIN (LOWER(name) gin_trgm_ops);
+CREATE INDEX categories_name_trgm_idx ON categories USING GIN (LOWER(name) gin_trgm_ops);
+
+-- Units sold in paid orders, refreshed together with product_co_purchases.
+CREATE MATERIALIZED VIEW product_popularity AS
+SELECT oi.product_id, SUM(oi.quantity)::BIGINT AS units_sold
+FROM order_items oi
+JOIN orders o ON o.id = oi.order_id
+WHERE o.status = 'paid'
+GROUP BY oi.product_id;
+
+CREATE UNIQUE INDEX product_popularity_product_idx ON product_popularity (product_id);
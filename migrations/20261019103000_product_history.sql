@@ -0,0 +1,79 @@
+-- Versioned audit trail of product changes.
+
+ALTER TABLE products
+    ADD COLUMN version INTEGER NOT NULL DEFAULT 1,
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE TABLE product_history (
+    id BIGSERIAL PRIMARY KEY,
+    -- No foreign key: history outlives deleted products.
+    product_id UUID NOT NULL,
+    version INTEGER NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('insert', 'update', 'delete')),
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    old_values JSONB,
+    new_values JSONB,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX product_history_product_idx ON product_history (product_id, version DESC);
+
+-- The acting user, set per transaction by the application with
+-- set_config('app.actor_id', ..., true).
+CREATE FUNCTION current_actor_id() RETURNS UUID AS $$
+    SELECT NULLIF(current_setting('app.actor_id', true), '')::UUID
+$$ LANGUAGE sql STABLE;
+
+-- Checkout reservations, order fulfilment and review aggregates touch
+-- products constantly; those bookkeeping updates are not product edits unless
+-- a user made them explicitly.
+CREATE FUNCTION product_change_is_tracked(old_row products, new_row products) RETURNS BOOLEAN AS $$
+    SELECT CASE
+        WHEN current_actor_id() IS NOT NULL THEN
+            (to_jsonb(new_row) - ARRAY['version', 'updated_at'])
+                IS DISTINCT FROM (to_jsonb(old_row) - ARRAY['version', 'updated_at'])
+        ELSE
+            (to_jsonb(new_row) - ARRAY['version', 'updated_at', 'stock_quantity', 'reserved_quantity', 'rating_average', 'rating_count'])
+                IS DISTINCT FROM (to_jsonb(old_row) - ARRAY['version', 'updated_at', 'stock_quantity', 'reserved_quantity', 'rating_average', 'rating_count'])
+    END
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION products_bump_version() RETURNS TRIGGER AS $$
+BEGIN
+    IF product_change_is_tracked(OLD, NEW) THEN
+        NEW.version := OLD.version + 1;
+        NEW.updated_at := now();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER products_bump_version
+    BEFORE UPDATE ON products
+    FOR EACH ROW EXECUTE FUNCTION products_bump_version();
+
+CREATE FUNCTION products_record_history() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO product_history (product_id, version, action, actor_id, new_values)
+        VALUES (NEW.id, NEW.version, 'insert', current_actor_id(), to_jsonb(NEW));
+    ELSIF TG_OP = 'UPDATE' THEN
+        IF NEW.version <> OLD.version THEN
+            INSERT INTO product_history (product_id, version, action, actor_id, old_values, new_values)
+            VALUES (NEW.id, NEW.version, 'update', current_actor_id(), to_jsonb(OLD), to_jsonb(NEW));
+        END IF;
+    ELSE
+        INSERT INTO product_history (product_id, version, action, actor_id, old_values)
+        VALUES (OLD.id, OLD.version, 'delete', current_actor_id(), to_jsonb(OLD));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER products_record_history
+    AFTER INSERT OR UPDATE OR DELETE ON products
+    FOR EACH ROW EXECUTE FUNCTION products_record_history();
+
+-- Baseline entry for products that existed before history was recorded.
+INSERT INTO product_history (product_id, version, action, new_values, changed_at)
+SELECT id, version, 'insert', to_jsonb(products), created_at FROM products;
@@ -0,0 +1,47 @@
+-- Scheduled sale prices, compare-at prices and a price history for
+-- "lowest price in the last 30 days" disclosure.
+
+ALTER TABLE products
+    ADD COLUMN compare_at_price BIGINT CHECK (compare_at_price >= 0);
+
+-- Sale prices are in the product's own currency. When windows overlap the
+-- lowest active price applies.
+CREATE TABLE product_sales (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    sale_price BIGINT NOT NULL CHECK (sale_price >= 0),
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (ends_at > starts_at)
+);
+
+CREATE INDEX product_sales_window_idx ON product_sales (product_id, starts_at, ends_at);
+
+-- Regular price as of `recorded_at`; a new row whenever price or currency change.
+CREATE TABLE product_price_history (
+    id BIGSERIAL PRIMARY KEY,
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    price BIGINT NOT NULL,
+    currency TEXT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX product_price_history_idx ON product_price_history (product_id, recorded_at DESC);
+
+CREATE FUNCTION products_record_price() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' OR NEW.price <> OLD.price OR NEW.currency <> OLD.currency THEN
+        INSERT INTO product_price_history (product_id, price, currency)
+        VALUES (NEW.id, NEW.price, NEW.currency);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER products_record_price
+    AFTER INSERT OR UPDATE OF price, currency ON products
+    FOR EACH ROW EXECUTE FUNCTION products_record_price();
+
+INSERT INTO product_price_history (product_id, price, currency, recorded_at)
+SELECT id, price, currency, created_at FROM products;
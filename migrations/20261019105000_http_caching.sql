@@ -0,0 +1,39 @@
+-- `updated_at` becomes the Last-Modified time of a product's public
+-- representation: it moves on every row change (stock and ratings included)
+-- and when the product's images, price list or sales change. `version` still
+-- only counts recorded edits.
+
+CREATE OR REPLACE FUNCTION products_bump_version() RETURNS TRIGGER AS $$
+BEGIN
+    IF product_change_is_tracked(OLD, NEW) THEN
+        NEW.version := OLD.version + 1;
+        NEW.updated_at := now();
+    ELSIF (to_jsonb(NEW) - 'updated_at') IS DISTINCT FROM (to_jsonb(OLD) - 'updated_at') THEN
+        NEW.updated_at := now();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION touch_product() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        UPDATE products SET updated_at = now() WHERE id = OLD.product_id;
+    ELSE
+        UPDATE products SET updated_at = now() WHERE id = NEW.product_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER product_images_touch_product
+    AFTER INSERT OR UPDATE OR DELETE ON product_images
+    FOR EACH ROW EXECUTE FUNCTION touch_product();
+
+CREATE TRIGGER product_prices_touch_product
+    AFTER INSERT OR UPDATE OR DELETE ON product_prices
+    FOR EACH ROW EXECUTE FUNCTION touch_product();
+
+CREATE TRIGGER product_sales_touch_product
+    AFTER INSERT OR UPDATE OR DELETE ON product_sales
+    FOR EACH ROW EXECUTE FUNCTION touch_product();
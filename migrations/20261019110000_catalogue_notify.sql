@@ -0,0 +1,49 @@
+-- Cache invalidation: every change visible in catalogue responses sends the
+-- product id on the `catalogue_changed` channel, or an empty payload when it
+-- affects every product. pg_notify folds identical payloads within a
+-- transaction, so bulk imports send one message per product.
+
+-- Checkout reservations only move `reserved_quantity`, which no response
+-- shows; they no longer count as a modification.
+CREATE OR REPLACE FUNCTION products_bump_version() RETURNS TRIGGER AS $$
+BEGIN
+    IF product_change_is_tracked(OLD, NEW) THEN
+        NEW.version := OLD.version + 1;
+        NEW.updated_at := now();
+    ELSIF (to_jsonb(NEW) - ARRAY['updated_at', 'reserved_quantity'])
+            IS DISTINCT FROM (to_jsonb(OLD) - ARRAY['updated_at', 'reserved_quantity']) THEN
+        NEW.updated_at := now();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION products_notify_change() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        PERFORM pg_notify('catalogue_changed', OLD.id::TEXT);
+    ELSIF TG_OP = 'INSERT' OR NEW.updated_at <> OLD.updated_at THEN
+        PERFORM pg_notify('catalogue_changed', NEW.id::TEXT);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER products_notify_change
+    AFTER INSERT OR UPDATE OR DELETE ON products
+    FOR EACH ROW EXECUTE FUNCTION products_notify_change();
+
+CREATE FUNCTION catalogue_notify_all() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('catalogue_changed', '');
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER exchange_rates_notify_change
+    AFTER INSERT OR UPDATE OR DELETE ON exchange_rates
+    FOR EACH STATEMENT EXECUTE FUNCTION catalogue_notify_all();
+
+CREATE TRIGGER attribute_definitions_notify_change
+    AFTER INSERT OR UPDATE OR DELETE ON attribute_definitions
+    FOR EACH STATEMENT EXECUTE FUNCTION catalogue_notify_all();
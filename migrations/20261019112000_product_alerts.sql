@@ -0,0 +1,22 @@
+-- Where alert and reminder emails go.
+ALTER TABLE users ADD COLUMN email TEXT UNIQUE;
+
+-- Back-in-stock and price-drop subscriptions. An alert fires once when its
+-- condition becomes true (`notified_at` is set) and is re-armed when the
+-- condition stops holding, so a flapping product does not flood inboxes.
+CREATE TABLE product_alerts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('back_in_stock', 'price_drop')),
+    -- Price drops fire when the effective price in `currency` reaches this.
+    target_price BIGINT CHECK (target_price >= 0),
+    currency TEXT,
+    unsubscribe_token TEXT NOT NULL UNIQUE,
+    notified_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (user_id, product_id, kind),
+    CHECK (kind <> 'price_drop' OR (target_price IS NOT NULL AND currency IS NOT NULL))
+);
+
+CREATE INDEX product_alerts_pending_idx ON product_alerts (kind) WHERE notified_at IS NULL;
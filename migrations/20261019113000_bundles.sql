@@ -0,0 +1,59 @@
+-- Bundles are products made of other products. A bundle has its own price
+-- but no stock of its own: its stock is how many complete kits the
+-- components' free stock can make, kept up to date by triggers. Checkout
+-- reserves and sells the components.
+ALTER TABLE products ADD COLUMN is_bundle BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE bundle_components (
+    bundle_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    component_id UUID NOT NULL REFERENCES products(id) ON DELETE RESTRICT,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    PRIMARY KEY (bundle_id, component_id),
+    CHECK (bundle_id <> component_id)
+);
+
+CREATE INDEX bundle_components_component_idx ON bundle_components (component_id);
+
+-- The bundle a component order item was sold as part of.
+ALTER TABLE order_items ADD COLUMN bundle_id UUID REFERENCES products(id) ON DELETE SET NULL;
+
+-- NULL (untracked) when no component tracks stock.
+CREATE FUNCTION bundle_stock(bundle UUID) RETURNS INTEGER AS $$
+    SELECT CASE WHEN COUNT(*) = 0 THEN 0 ELSE MIN(
+        CASE WHEN c.available THEN (c.stock_quantity - c.reserved_quantity) / bc.quantity ELSE 0 END
+    )::INTEGER END
+    FROM bundle_components bc
+    JOIN products c ON c.id = bc.component_id
+    WHERE bc.bundle_id = bundle
+$$ LANGUAGE sql STABLE;
+
+-- Named to sort before products_bump_version, so derived stock changes count
+-- as modifications of the bundle.
+CREATE FUNCTION bundles_derive_stock() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.is_bundle THEN
+        NEW.stock_quantity := bundle_stock(NEW.id);
+        NEW.reserved_quantity := 0;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER bundles_derive_stock
+    BEFORE INSERT OR UPDATE ON products
+    FOR EACH ROW EXECUTE FUNCTION bundles_derive_stock();
+
+CREATE FUNCTION bundles_refresh_stock() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE products b SET stock_quantity = bundle_stock(b.id)
+    WHERE b.is_bundle
+      AND b.id IN (SELECT bundle_id FROM bundle_components WHERE component_id = NEW.id)
+      AND b.stock_quantity IS DISTINCT FROM bundle_stock(b.id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER products_refresh_bundle_stock
+    AFTER UPDATE OF stock_quantity, reserved_quantity, available ON products
+    FOR EACH ROW WHEN (NOT NEW.is_bundle)
+    EXECUTE FUNCTION bundles_refresh_stock();
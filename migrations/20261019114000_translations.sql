@@ -0,0 +1,22 @@
+-- Per-locale names and descriptions. The base columns on products and
+-- categories hold the default locale; a missing translation falls back to them.
+CREATE TABLE product_translations (
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    locale TEXT NOT NULL CHECK (locale ~ '^[a-z]{2,3}(-[a-z0-9]{2,8})*$'),
+    name TEXT NOT NULL,
+    description TEXT,
+    PRIMARY KEY (product_id, locale)
+);
+
+CREATE TABLE category_translations (
+    category_id UUID NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    locale TEXT NOT NULL CHECK (locale ~ '^[a-z]{2,3}(-[a-z0-9]{2,8})*$'),
+    name TEXT NOT NULL,
+    PRIMARY KEY (category_id, locale)
+);
+
+-- Translated content is part of the product's representation: move its
+-- Last-Modified time and invalidate cached copies.
+CREATE TRIGGER product_translations_touch_product
+    AFTER INSERT OR UPDATE OR DELETE ON product_translations
+    FOR EACH ROW EXECUTE FUNCTION touch_product();
@@ -0,0 +1,32 @@
+-- Digital products are delivered as files instead of being shipped. The
+-- files live in the blob store under `digital/` and are never served from
+-- the public media route; paying for an order grants each buyer a limited
+-- number of downloads per digital product, fetched through signed links.
+ALTER TABLE products ADD COLUMN is_digital BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE product_files (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    file_name TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX product_files_product_idx ON product_files (product_id);
+
+-- Counted per product rather than per file so replacing a file keeps
+-- the buyer's remaining downloads.
+CREATE TABLE download_grants (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    downloads_used INTEGER NOT NULL DEFAULT 0,
+    max_downloads INTEGER NOT NULL CHECK (max_downloads > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (order_id, product_id),
+    CHECK (downloads_used BETWEEN 0 AND max_downloads)
+);
+
+CREATE INDEX download_grants_user_idx ON download_grants (user_id);
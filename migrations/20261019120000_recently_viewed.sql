@@ -0,0 +1,13 @@
+-- The last products each user looked at, newest first. One row per
+-- product; viewing it again only moves it to the front. Rows beyond the
+-- per-user limit are trimmed as views are written.
+ALTER TABLE users ADD COLUMN track_recently_viewed BOOLEAN NOT NULL DEFAULT TRUE;
+
+CREATE TABLE recently_viewed (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    viewed_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, product_id)
+);
+
+CREATE INDEX recently_viewed_user_idx ON recently_viewed (user_id, viewed_at DESC);
@@ -0,0 +1,62 @@
+-- Customer questions about a product and their answers. Customers' posts
+-- wait for moderation before they are shown; staff answers are published
+-- straight away. Answers from customers are only accepted from buyers.
+
+CREATE TABLE product_questions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    upvotes INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX product_questions_product_idx ON product_questions (product_id, status, upvotes DESC);
+CREATE INDEX product_questions_pending_idx ON product_questions (created_at) WHERE status = 'pending';
+
+CREATE TABLE product_answers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    question_id UUID NOT NULL REFERENCES product_questions(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    author_role TEXT NOT NULL CHECK (author_role IN ('staff', 'verified_buyer')),
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    upvotes INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX product_answers_question_idx ON product_answers (question_id);
+CREATE INDEX product_answers_pending_idx ON product_answers (created_at) WHERE status = 'pending';
+
+CREATE TABLE question_votes (
+    question_id UUID NOT NULL REFERENCES product_questions(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (question_id, user_id)
+);
+
+CREATE TABLE answer_votes (
+    answer_id UUID NOT NULL REFERENCES product_answers(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (answer_id, user_id)
+);
+
+-- The product detail response lists the top questions, so they are part of
+-- the product's representation like its images and translations.
+CREATE TRIGGER product_questions_touch_product
+    AFTER INSERT OR UPDATE OR DELETE ON product_questions
+    FOR EACH ROW EXECUTE FUNCTION touch_product();
+
+CREATE FUNCTION product_answers_touch_product() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE products p SET updated_at = now()
+    FROM product_questions q
+    WHERE q.id = CASE WHEN TG_OP = 'DELETE' THEN OLD.question_id ELSE NEW.question_id END
+      AND p.id = q.product_id;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER product_answers_touch_product
+    AFTER INSERT OR UPDATE OR DELETE ON product_answers
+    FOR EACH ROW EXECUTE FUNCTION product_answers_touch_product();
@@ -0,0 +1,35 @@
+-- One cart per user and one row per product in a cart. Existing duplicates
+-- are folded together first: extra carts into the user's oldest cart, and
+-- repeated products into one row with the quantities added up.
+
+WITH ranked AS (
+    SELECT id, first_value(id) OVER (PARTITION BY user_id ORDER BY created_at, id) AS keep
+    FROM shopping_cart
+)
+UPDATE cart_items ci SET cart_id = r.keep
+FROM ranked r
+WHERE ci.cart_id = r.id AND r.id <> r.keep;
+
+DELETE FROM shopping_cart s
+USING (
+    SELECT id, first_value(id) OVER (PARTITION BY user_id ORDER BY created_at, id) AS keep
+    FROM shopping_cart
+) r
+WHERE s.id = r.id AND r.id <> r.keep;
+
+CREATE TEMPORARY TABLE cart_item_duplicates ON COMMIT DROP AS
+SELECT cart_id, product_id, SUM(quantity) AS quantity, (array_agg(id ORDER BY added_at, id))[1] AS keep
+FROM cart_items
+GROUP BY cart_id, product_id
+HAVING COUNT(*) > 1;
+
+UPDATE cart_items ci SET quantity = d.quantity
+FROM cart_item_duplicates d
+WHERE ci.id = d.keep;
+
+DELETE FROM cart_items ci
+USING cart_item_duplicates d
+WHERE ci.cart_id = d.cart_id AND ci.product_id = d.product_id AND ci.id <> d.keep;
+
+ALTER TABLE shopping_cart ADD CONSTRAINT shopping_cart_user_id_key UNIQUE (user_id);
+ALTER TABLE cart_items ADD CONSTRAINT cart_items_cart_product_key UNIQUE (cart_id, product_id);
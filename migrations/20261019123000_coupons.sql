@@ -0,0 +1,40 @@
+-- Discount codes. Amounts (`amount_off`, `min_order_amount`) are minor units
+-- of the base currency and are converted for carts in other currencies.
+-- Empty `product_ids` and `category_ids` make every product eligible.
+
+CREATE TABLE coupons (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    code TEXT NOT NULL UNIQUE CHECK (code ~ '^[A-Z0-9_-]{3,32}$'),
+    kind TEXT NOT NULL CHECK (kind IN ('percent', 'fixed_amount', 'free_shipping')),
+    percent_off INTEGER CHECK (percent_off BETWEEN 1 AND 100),
+    amount_off BIGINT CHECK (amount_off > 0),
+    starts_at TIMESTAMPTZ,
+    ends_at TIMESTAMPTZ,
+    max_uses INTEGER CHECK (max_uses > 0),
+    max_uses_per_user INTEGER CHECK (max_uses_per_user > 0),
+    min_order_amount BIGINT CHECK (min_order_amount > 0),
+    product_ids UUID[] NOT NULL DEFAULT '{}',
+    category_ids UUID[] NOT NULL DEFAULT '{}',
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK ((kind = 'percent') = (percent_off IS NOT NULL)),
+    CHECK ((kind = 'fixed_amount') = (amount_off IS NOT NULL)),
+    CHECK (ends_at IS NULL OR starts_at IS NULL OR ends_at > starts_at)
+);
+
+-- The code a cart will be checked out with.
+ALTER TABLE shopping_cart ADD COLUMN coupon_id UUID REFERENCES coupons(id) ON DELETE SET NULL;
+
+-- A use of a code. Pending orders hold their use; it is given back when
+-- the order expires or fails.
+CREATE TABLE coupon_redemptions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    coupon_id UUID NOT NULL REFERENCES coupons(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    order_id UUID NOT NULL UNIQUE REFERENCES orders(id) ON DELETE CASCADE,
+    amount BIGINT NOT NULL,
+    currency TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX coupon_redemptions_coupon_user_idx ON coupon_redemptions (coupon_id, user_id);
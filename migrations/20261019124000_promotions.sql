@@ -0,0 +1,50 @@
+-- Automatic promotions, evaluated on every cart without a code. Amounts are
+-- minor units of the base currency. Empty `product_ids` and `category_ids`
+-- make every product eligible.
+--
+--   buy_x_get_y      every `buy_quantity` eligible units give `get_quantity`
+--                    more at `get_percent_off` (the cheapest units are discounted)
+--   spend_threshold  an eligible spend of `threshold_amount` earns
+--                    `percent_off` or `amount_off`
+--   quantity_tier    the highest tier reached by the eligible quantity gives
+--                    its `percent_off`
+--   free_shipping    an eligible spend of `threshold_amount` waives shipping
+--
+-- Promotions run from the highest `priority` down. A non-stackable promotion
+-- only applies when nothing has applied before it and ends the run.
+
+CREATE TABLE promotions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL CHECK (name <> ''),
+    kind TEXT NOT NULL CHECK (kind IN ('buy_x_get_y', 'spend_threshold', 'quantity_tier', 'free_shipping')),
+    priority INTEGER NOT NULL DEFAULT 0,
+    stackable BOOLEAN NOT NULL DEFAULT TRUE,
+    buy_quantity INTEGER CHECK (buy_quantity > 0),
+    get_quantity INTEGER CHECK (get_quantity > 0),
+    get_percent_off INTEGER NOT NULL DEFAULT 100 CHECK (get_percent_off BETWEEN 1 AND 100),
+    threshold_amount BIGINT CHECK (threshold_amount > 0),
+    percent_off INTEGER CHECK (percent_off BETWEEN 1 AND 100),
+    amount_off BIGINT CHECK (amount_off > 0),
+    product_ids UUID[] NOT NULL DEFAULT '{}',
+    category_ids UUID[] NOT NULL DEFAULT '{}',
+    starts_at TIMESTAMPTZ,
+    ends_at TIMESTAMPTZ,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK ((kind = 'buy_x_get_y') = (buy_quantity IS NOT NULL AND get_quantity IS NOT NULL)),
+    CHECK (kind <> 'spend_threshold'
+        OR (threshold_amount IS NOT NULL AND (percent_off IS NULL) <> (amount_off IS NULL))),
+    CHECK (kind <> 'free_shipping' OR (threshold_amount IS NOT NULL AND percent_off IS NULL AND amount_off IS NULL)),
+    CHECK (kind IN ('spend_threshold', 'free_shipping')
+        OR (threshold_amount IS NULL AND percent_off IS NULL AND amount_off IS NULL)),
+    CHECK (ends_at IS NULL OR starts_at IS NULL OR ends_at > starts_at)
+);
+
+CREATE TABLE promotion_tiers (
+    promotion_id UUID NOT NULL REFERENCES promotions(id) ON DELETE CASCADE,
+    min_quantity INTEGER NOT NULL CHECK (min_quantity > 1),
+    percent_off INTEGER NOT NULL CHECK (percent_off BETWEEN 1 AND 100),
+    PRIMARY KEY (promotion_id, min_quantity)
+);
+
+CREATE INDEX promotions_active_idx ON promotions (priority DESC) WHERE active;
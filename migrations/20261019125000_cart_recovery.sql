@@ -0,0 +1,49 @@
+-- Last time the cart's contents changed. Drives reminders and expiry.
+ALTER TABLE shopping_cart
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    -- Set when a reminder goes out; a reminder is due again only after
+    -- the cart changes and goes idle once more.
+    ADD COLUMN reminder_sent_at TIMESTAMPTZ;
+
+UPDATE shopping_cart c SET updated_at = GREATEST(
+    c.created_at,
+    COALESCE((SELECT MAX(i.added_at) FROM cart_items i WHERE i.cart_id = c.id), c.created_at)
+);
+
+CREATE INDEX shopping_cart_updated_at_idx ON shopping_cart (updated_at);
+
+CREATE OR REPLACE FUNCTION touch_shopping_cart() RETURNS trigger AS $$
+BEGIN
+    UPDATE shopping_cart SET updated_at = now()
+    WHERE id = CASE WHEN TG_OP = 'DELETE' THEN OLD.cart_id ELSE NEW.cart_id END;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER cart_items_touch_cart
+    AFTER INSERT OR UPDATE OR DELETE ON cart_items
+    FOR EACH ROW EXECUTE FUNCTION touch_shopping_cart();
+
+ALTER TABLE users ADD COLUMN cart_reminders BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- One reminder email. The items are kept so the restore link still works
+-- after the cart has expired. `order_id` is the paid order it recovered.
+CREATE TABLE cart_reminders (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    cart_id UUID REFERENCES shopping_cart(id) ON DELETE SET NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    restored_at TIMESTAMPTZ,
+    order_id UUID UNIQUE REFERENCES orders(id) ON DELETE SET NULL,
+    recovered_at TIMESTAMPTZ
+);
+
+CREATE INDEX cart_reminders_user_idx ON cart_reminders (user_id, sent_at DESC);
+
+CREATE TABLE cart_reminder_items (
+    reminder_id UUID NOT NULL REFERENCES cart_reminders(id) ON DELETE CASCADE,
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    PRIMARY KEY (reminder_id, product_id)
+);
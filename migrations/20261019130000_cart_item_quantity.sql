@@ -0,0 +1,6 @@
+-- Removing from a cart used to be able to store zero or negative quantities.
+-- Those lines are dropped, and the quantity must now be positive.
+
+DELETE FROM cart_items WHERE quantity <= 0;
+
+ALTER TABLE cart_items ADD CONSTRAINT cart_items_quantity_check CHECK (quantity > 0);
@@ -0,0 +1,223 @@
+use actix_web::{web, HttpResponse, Responder};
+use uuid::Uuid;
+use validator::ValidateEmail;
+use crate::AppState;
+use crate::alert::models::{AlertKind, EmailUpdate, NewAlert, ProductAlert};
+use crate::auth::models::AuthenticatedUser;
+use crate::pricing::models::RequestCurrency;
+use crate::pricing::rates::prices_in;
+use crate::routes::models::ApiResponse;
+
+pub async fn set_email(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    payload: web::Json<EmailUpdate>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let email = payload.email.trim();
+    if !email.validate_email() {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Invalid email address".to_string(),
+            data: "No Data".to_string(),
+        });
+    }
+
+    let result = sqlx::query("UPDATE users SET email = $1 WHERE id = $2")
+        .bind(email)
+        .bind(user_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Email saved".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => HttpResponse::Conflict().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Email is already in use".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn subscribe(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    product_id: web::Path<Uuid>,
+    payload: web::Json<NewAlert>,
+    currency: RequestCurrency,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+    let product_id = product_id.into_inner();
+
+    let has_email = sqlx::query_scalar::<_, bool>("SELECT email IS NOT NULL FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(&data.db_pool)
+        .await;
+
+    match has_email {
+        Ok(Some(true)) => {}
+        Ok(_) => return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Save an email address with PUT /me/email first".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+
+    let in_stock = sqlx::query_scalar::<_, bool>(
+        "SELECT available AND COALESCE(stock_quantity - reserved_quantity > 0, TRUE) FROM products WHERE id = $1"
+    )
+        .bind(product_id)
+        .fetch_optional(&data.db_pool)
+        .await;
+
+    let in_stock = match in_stock {
+        Ok(Some(in_stock)) => in_stock,
+        Ok(None) => return HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let (target_price, alert_currency) = match payload.kind {
+        AlertKind::BackInStock if in_stock => return HttpResponse::Conflict().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product is already in stock".to_string(),
+            data: "No Data".to_string(),
+        }),
+        AlertKind::BackInStock => (None, None),
+        AlertKind::PriceDrop => {
+            let current = match prices_in(&data.db_pool, data.base_currency, &[product_id], currency.0).await {
+                Ok(quotes) => quotes[&product_id].price,
+                Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+            };
+            let target = payload.target_price.unwrap_or(current.minor_units - 1);
+            if target < 0 || target >= current.minor_units {
+                return HttpResponse::BadRequest().json(ApiResponse {
+                    status: "Error".to_string(),
+                    msg: format!("Target price must be below the current price of {}", current),
+                    data: "No Data".to_string(),
+                });
+            }
+            (Some(target), Some(currency.0.as_str()))
+        }
+    };
+
+    // Subscribing again updates the target and re-arms the alert
+    let alert = sqlx::query_as::<_, ProductAlert>(
+        "INSERT INTO product_alerts (user_id, product_id, kind, target_price, currency, unsubscribe_token) \
+         VALUES ($1, $2, $3, $4, $5, $6) \
+         ON CONFLICT (user_id, product_id, kind) DO UPDATE \
+         SET target_price = EXCLUDED.target_price, currency = EXCLUDED.currency, notified_at = NULL \
+         RETURNING *"
+    )
+        .bind(user_id)
+        .bind(product_id)
+        .bind(payload.kind.as_str())
+        .bind(target_price)
+        .bind(alert_currency)
+        .bind(Uuid::new_v4().simple().to_string())
+        .fetch_one(&data.db_pool)
+        .await;
+
+    match alert {
+        Ok(alert) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Alert saved".to_string(),
+            data: alert,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn get_my_alerts(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let alerts = sqlx::query_as::<_, ProductAlert>(
+        "SELECT * FROM product_alerts WHERE user_id = $1 ORDER BY created_at DESC"
+    )
+        .bind(user_id)
+        .fetch_all(&data.db_pool)
+        .await;
+
+    match alerts {
+        Ok(alerts) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Your alerts".to_string(),
+            data: alerts,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn delete_alert(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    alert_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let result = sqlx::query("DELETE FROM product_alerts WHERE id = $1 AND user_id = $2")
+        .bind(alert_id.into_inner())
+        .bind(user_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(done) if done.rows_affected() == 0 => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Alert not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Alert removed".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+// Target of the link in alert emails; works without logging in
+pub async fn unsubscribe(
+    data: web::Data<AppState>,
+    token: web::Path<String>,
+) -> impl Responder {
+    let result = sqlx::query("DELETE FROM product_alerts WHERE unsubscribe_token = $1")
+        .bind(token.as_str())
+        .execute(&data.db_pool)
+        .await;
+
+    // Repeated clicks on the same link are not an error
+    match result {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "You will not receive this alert any more".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
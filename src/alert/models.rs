@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    BackInStock,
+    PriceDrop,
+}
+
+impl AlertKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::BackInStock => "back_in_stock",
+            AlertKind::PriceDrop => "price_drop",
+        }
+    }
+}
+
+impl TryFrom<String> for AlertKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "back_in_stock" => Ok(AlertKind::BackInStock),
+            "price_drop" => Ok(AlertKind::PriceDrop),
+            _ => Err(format!("Unknown alert kind: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ProductAlert {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    #[sqlx(try_from = "String")]
+    pub kind: AlertKind,
+    pub target_price: Option<i64>,
+    pub currency: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub notified_at: Option<DateTime<Utc>>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+// A price drop without target_price fires on any drop below the current price
+#[derive(Debug, Deserialize)]
+pub struct NewAlert {
+    pub kind: AlertKind,
+    pub target_price: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmailUpdate {
+    pub email: String,
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use log::{error, info, warn};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::mailer::{app_base_url, Email, Mailer};
+use crate::money::{Currency, Money};
+use crate::pricing::rates::prices_in;
+
+#[derive(Debug, sqlx::FromRow)]
+struct DueAlert {
+    id: Uuid,
+    kind: String,
+    email: String,
+    product_id: Uuid,
+    product_name: String,
+    unsubscribe_token: String,
+}
+
+const DUE_ALERT_RETURNING: &str =
+    "RETURNING a.id, a.kind, u.email, p.id AS product_id, p.name AS product_name, a.unsubscribe_token";
+
+// Alerts are claimed by setting notified_at before sending, so concurrent runs never mail one twice
+pub async fn send_due_alerts(pool: &PgPool, mailer: &dyn Mailer, base: Currency) -> Result<usize, sqlx::Error> {
+    sqlx::query(
+        "UPDATE product_alerts a SET notified_at = NULL FROM products p \
+         WHERE a.product_id = p.id AND a.kind = 'back_in_stock' AND a.notified_at IS NOT NULL \
+           AND NOT (p.available AND COALESCE(p.stock_quantity - p.reserved_quantity > 0, TRUE))"
+    )
+        .execute(pool)
+        .await?;
+
+    let mut due = sqlx::query_as::<_, DueAlert>(&format!(
+        "UPDATE product_alerts a SET notified_at = now() FROM users u, products p \
+         WHERE u.id = a.user_id AND p.id = a.product_id AND u.email IS NOT NULL \
+           AND a.kind = 'back_in_stock' AND a.notified_at IS NULL \
+           AND p.available AND COALESCE(p.stock_quantity - p.reserved_quantity > 0, TRUE) {}",
+        DUE_ALERT_RETURNING
+    ))
+        .fetch_all(pool)
+        .await?;
+
+    let (ready, rearm) = price_drop_changes(pool, base).await?;
+    sqlx::query("UPDATE product_alerts SET notified_at = NULL WHERE id = ANY($1)")
+        .bind(&rearm)
+        .execute(pool)
+        .await?;
+    due.extend(
+        sqlx::query_as::<_, DueAlert>(&format!(
+            "UPDATE product_alerts a SET notified_at = now() FROM users u, products p \
+             WHERE u.id = a.user_id AND p.id = a.product_id AND u.email IS NOT NULL \
+               AND a.id = ANY($1) AND a.notified_at IS NULL {}",
+            DUE_ALERT_RETURNING
+        ))
+            .bind(&ready)
+            .fetch_all(pool)
+            .await?,
+    );
+
+    let base_url = app_base_url();
+    let mut sent = 0;
+    for alert in due {
+        let subject = match alert.kind.as_str() {
+            "price_drop" => format!("Price drop: {}", alert.product_name),
+            _ => format!("Back in stock: {}", alert.product_name),
+        };
+        let email = Email {
+            to: alert.email.clone(),
+            subject,
+            body: format!(
+                "Good news! {} is ready for you.\n\n{}/product/{}\n\nTo stop these alerts: {}/alerts/unsubscribe/{}\n",
+                alert.product_name, base_url, alert.product_id, base_url, alert.unsubscribe_token
+            ),
+        };
+
+        match mailer.send(&email).await {
+            Ok(()) => sent += 1,
+            Err(e) => {
+                warn!("Failed to send alert {}: {}", alert.id, e);
+                sqlx::query("UPDATE product_alerts SET notified_at = NULL WHERE id = $1")
+                    .bind(alert.id)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+    }
+    Ok(sent)
+}
+
+// Reached targets not yet sent, and sent ones whose price has risen above the target again
+async fn price_drop_changes(pool: &PgPool, base: Currency) -> Result<(Vec<Uuid>, Vec<Uuid>), sqlx::Error> {
+    let alerts = sqlx::query_as::<_, (Uuid, Uuid, i64, String, bool)>(
+        "SELECT id, product_id, target_price, currency, notified_at IS NOT NULL \
+         FROM product_alerts WHERE kind = 'price_drop'"
+    )
+        .fetch_all(pool)
+        .await?;
+
+    let mut by_currency: HashMap<String, Vec<Uuid>> = HashMap::new();
+    for (_, product_id, _, currency, _) in &alerts {
+        by_currency.entry(currency.clone()).or_default().push(*product_id);
+    }
+
+    let mut prices: HashMap<(Uuid, String), Money> = HashMap::new();
+    for (code, mut product_ids) in by_currency {
+        let currency = match code.parse::<Currency>() {
+            Ok(currency) => currency,
+            Err(e) => {
+                warn!("Skipping price alerts: {}", e);
+                continue;
+            }
+        };
+        product_ids.sort();
+        product_ids.dedup();
+        match prices_in(pool, base, &product_ids, currency).await {
+            Ok(quotes) => prices.extend(quotes.into_iter().map(|(id, quote)| ((id, code.clone()), quote.price))),
+            Err(e) => warn!("Skipping {} price alerts: {}", code, e),
+        }
+    }
+
+    let mut ready = Vec::new();
+    let mut rearm = Vec::new();
+    for (id, product_id, target_price, currency, notified) in alerts {
+        let Some(price) = prices.get(&(product_id, currency)) else { continue };
+        match (price.minor_units <= target_price, notified) {
+            (true, false) => ready.push(id),
+            (false, true) => rearm.push(id),
+            _ => {}
+        }
+    }
+    Ok((ready, rearm))
+}
+
+pub fn spawn_alert_job(pool: PgPool, mailer: Arc<dyn Mailer>, base: Currency) {
+    let period = env::var("ALERT_CHECK_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(300);
+
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(Duration::from_secs(period));
+        loop {
+            interval.tick().await;
+            match send_due_alerts(&pool, mailer.as_ref(), base).await {
+                Ok(0) => {}
+                Ok(sent) => info!("Sent {} product alerts", sent),
+                Err(e) => error!("Failed to process product alerts: {}", e),
+            }
+        }
+    });
+}
@@ -60,7 +60,7 @@ pub async fn log_in(
                 verify_pwd_salted(&payload.password, &user.password).unwrap_or(false);
 
             if password_verified {
-                let auth_token = generate_jwt(&user.id.to_string());
+                let auth_token = generate_jwt(&user.id.to_string(), &user.role);
 
                 if let Ok(token) = auth_token {
                     HttpResponse::Ok().json(ApiResponse {
@@ -1,16 +1,17 @@
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-use crate::auth::models::{AuthenticatedUser, Claims};
+use crate::auth::models::{AdminUser, AuthenticatedUser, Claims};
 use std::env;
 use std::future::{ready, Ready};
 use actix_web::{Error, FromRequest, HttpRequest};
 use actix_web::dev::Payload;
 
-pub fn generate_jwt(user_id: &str) -> jsonwebtoken::errors::Result<String> {
+pub fn generate_jwt(user_id: &str, role: &str) -> jsonwebtoken::errors::Result<String> {
     let secret_key = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
     let expiration = chrono::Utc::now().checked_add_signed(chrono::Duration::days(1)).unwrap().timestamp() as usize;
     let claims = Claims {
         sub: user_id.to_owned(),
         exp: expiration,
+        role: role.to_owned(),
     };
     encode(&Header::default(), &claims, &EncodingKey::from_secret(secret_key.as_ref()))
 }
@@ -49,3 +50,21 @@ impl FromRequest for AuthenticatedUser {
         ready(Err(actix_web::error::ErrorUnauthorized("Authorization header missing or invalid")))
     }
 }
+
+impl FromRequest for AdminUser {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let user = match AuthenticatedUser::from_request(req, payload).into_inner() {
+            Ok(user) => user,
+            Err(e) => return ready(Err(e)),
+        };
+
+        if user.claims.role == "admin" {
+            ready(Ok(AdminUser { claims: user.claims }))
+        } else {
+            ready(Err(actix_web::error::ErrorForbidden("Admin access required")))
+        }
+    }
+}
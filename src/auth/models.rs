@@ -5,7 +5,8 @@ use uuid::Uuid;
 pub struct User {
     pub id: Uuid,
     pub username: String,
-    pub password: String
+    pub password: String,
+    pub role: String,
 }
 
 
@@ -19,6 +20,8 @@ pub struct SignUp {
 pub struct Claims {
     pub sub: String,
     pub exp: usize,
+    #[serde(default)]
+    pub role: String,
 }
 
 #[derive(Serialize)]
@@ -30,3 +33,7 @@ pub struct LogInResponse {
 pub struct AuthenticatedUser {
     pub claims: Claims,
 }
+
+pub struct AdminUser {
+    pub claims: Claims,
+}
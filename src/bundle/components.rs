@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::bundle::models::BundleComponent;
+use crate::money::{Currency, Money};
+use crate::order::models::OrderLine;
+use crate::pricing::models::PricingError;
+use crate::pricing::rates::prices_in;
+
+pub async fn components_for_products(
+    pool: &PgPool,
+    product_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<BundleComponent>>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (Uuid, Uuid, String, i32)>(
+        "SELECT bc.bundle_id, bc.component_id, p.name, bc.quantity \
+         FROM bundle_components bc JOIN products p ON p.id = bc.component_id \
+         WHERE bc.bundle_id = ANY($1) ORDER BY p.name"
+    )
+        .bind(product_ids)
+        .fetch_all(pool)
+        .await?;
+
+    let mut components: HashMap<Uuid, Vec<BundleComponent>> = HashMap::new();
+    for (bundle_id, product_id, name, quantity) in rows {
+        components.entry(bundle_id).or_default().push(BundleComponent { product_id, name, quantity });
+    }
+    Ok(components)
+}
+
+// Orders record and reserve the components that actually ship
+pub async fn expand_bundles(
+    pool: &PgPool,
+    base: Currency,
+    currency: Currency,
+    lines: Vec<OrderLine>,
+) -> Result<Vec<OrderLine>, PricingError> {
+    let ids: Vec<Uuid> = lines.iter().map(|line| line.product_id).collect();
+    let components = components_for_products(pool, &ids).await?;
+    if components.is_empty() {
+        return Ok(lines);
+    }
+
+    let component_ids: Vec<Uuid> = components.values().flatten().map(|c| c.product_id).collect();
+    let prices = prices_in(pool, base, &component_ids, currency).await?;
+
+    let mut expanded = Vec::with_capacity(lines.len());
+    for line in lines {
+        let Some(parts) = components.get(&line.product_id) else {
+            expanded.push(line);
+            continue;
+        };
+        let parts: Vec<(Uuid, i32, Money)> = parts
+            .iter()
+            .map(|c| (c.product_id, c.quantity, prices[&c.product_id].price))
+            .collect();
+        for (product_id, quantity, price) in split_price(line.price, &parts) {
+            expanded.push(OrderLine {
+                product_id,
+                quantity: quantity * line.quantity,
+                price,
+                bundle_id: Some(line.product_id),
+            });
+        }
+    }
+    Ok(expanded)
+}
+
+// Leftover minor units go to the smallest quantities, and whatever still does not divide evenly goes
+// to one unit split off into its own line, so the lines always add up to the bundle price
+fn split_price(bundle_price: Money, parts: &[(Uuid, i32, Money)]) -> Vec<(Uuid, i32, Money)> {
+    let weights: Vec<i128> = parts
+        .iter()
+        .map(|(_, quantity, price)| price.minor_units as i128 * *quantity as i128)
+        .collect();
+    let total_weight: i128 = weights.iter().sum();
+
+    // Free components share the price by quantity
+    let weights: Vec<i128> = if total_weight > 0 {
+        weights
+    } else {
+        parts.iter().map(|(_, quantity, _)| *quantity as i128).collect()
+    };
+    let total_weight: i128 = weights.iter().sum();
+
+    let mut unit_prices = Vec::with_capacity(parts.len());
+    let mut leftover = bundle_price.minor_units as i128;
+    for ((_, quantity, _), weight) in parts.iter().zip(&weights) {
+        let share = bundle_price.minor_units as i128 * weight / total_weight;
+        let unit = share / *quantity as i128;
+        leftover -= unit * *quantity as i128;
+        unit_prices.push(unit);
+    }
+
+    let mut by_quantity: Vec<usize> = (0..parts.len()).collect();
+    by_quantity.sort_by_key(|&i| parts[i].1);
+    for &i in &by_quantity {
+        let quantity = parts[i].1 as i128;
+        unit_prices[i] += leftover / quantity;
+        leftover %= quantity;
+    }
+
+    let money = |unit: i128| Money::new(unit as i64, bundle_price.currency);
+    let mut split = Vec::with_capacity(parts.len() + 1);
+    for (i, ((product_id, quantity, _), unit)) in parts.iter().zip(unit_prices).enumerate() {
+        if leftover > 0 && by_quantity.first() == Some(&i) {
+            split.push((*product_id, *quantity - 1, money(unit)));
+            split.push((*product_id, 1, money(unit + leftover)));
+        } else {
+            split.push((*product_id, *quantity, money(unit)));
+        }
+    }
+    split
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Currency;
+
+    fn inr(amount: i64) -> Money {
+        Money::new(amount, Currency::Inr)
+    }
+
+    fn total(split: &[(Uuid, i32, Money)]) -> i64 {
+        split.iter().map(|(_, quantity, price)| price.minor_units * *quantity as i64).sum()
+    }
+
+    #[test]
+    fn uneven_weights_add_up_to_the_bundle_price() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let split = split_price(inr(1000), &[(a, 1, inr(333)), (b, 1, inr(500)), (c, 1, inr(167))]);
+        assert_eq!(total(&split), 1000);
+        assert_eq!(split, vec![(a, 1, inr(333)), (b, 1, inr(500)), (c, 1, inr(167))]);
+    }
+
+    #[test]
+    fn free_components_share_the_price_by_quantity() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let split = split_price(inr(900), &[(a, 1, inr(0)), (b, 2, inr(0))]);
+        assert_eq!(split, vec![(a, 1, inr(300)), (b, 2, inr(300))]);
+    }
+
+    #[test]
+    fn shortfall_that_does_not_divide_is_split_into_its_own_line() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let split = split_price(inr(1003), &[(a, 3, inr(100)), (b, 4, inr(100))]);
+        assert_eq!(total(&split), 1003);
+        assert_eq!(split, vec![(a, 2, inr(143)), (a, 1, inr(145)), (b, 4, inr(143))]);
+    }
+
+    #[test]
+    fn quantities_above_one_keep_a_single_line_when_they_divide() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let split = split_price(inr(1200), &[(a, 2, inr(250)), (b, 3, inr(100))]);
+        assert_eq!(total(&split), 1200);
+        assert_eq!(split, vec![(a, 2, inr(375)), (b, 3, inr(150))]);
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashSet;
+use actix_web::{web, HttpResponse, Responder};
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::AdminUser;
+use crate::bundle::components::components_for_products;
+use crate::bundle::models::BundleUpdate;
+use crate::product::history::begin_as;
+use crate::routes::models::ApiResponse;
+
+pub async fn set_bundle_components(
+    data: web::Data<AppState>,
+    admin: AdminUser,
+    id: web::Path<Uuid>,
+    payload: web::Json<BundleUpdate>,
+) -> impl Responder {
+    let bundle_id = id.into_inner();
+
+    let mut seen = HashSet::new();
+    for component in &payload.components {
+        if component.quantity < 1 || component.product_id == bundle_id || !seen.insert(component.product_id) {
+            return HttpResponse::BadRequest().json(ApiResponse {
+                status: "Error".to_string(),
+                msg: "Components must be distinct other products with a quantity of at least 1".to_string(),
+                data: "No Data".to_string(),
+            });
+        }
+    }
+
+    let mut tx = match begin_as(&data.db_pool, Uuid::parse_str(&admin.claims.sub).ok()).await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    // Bundles are one level deep: no bundle inside a bundle, in either direction
+    let component_ids: Vec<Uuid> = payload.components.iter().map(|c| c.product_id).collect();
+    let nesting = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM products WHERE id = ANY($1) AND is_bundle) \
+             OR (cardinality($1) > 0 AND EXISTS (SELECT 1 FROM bundle_components WHERE component_id = $2))"
+    )
+        .bind(&component_ids)
+        .bind(bundle_id)
+        .fetch_one(&mut *tx)
+        .await;
+
+    match nesting {
+        Ok(false) => {}
+        Ok(true) => return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Bundles cannot contain or be part of other bundles".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+
+    if let Err(e) = sqlx::query("DELETE FROM bundle_components WHERE bundle_id = $1")
+        .bind(bundle_id)
+        .execute(&mut *tx)
+        .await
+    {
+        return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+    }
+
+    let quantities: Vec<i32> = payload.components.iter().map(|c| c.quantity).collect();
+    let inserted = sqlx::query(
+        "INSERT INTO bundle_components (bundle_id, component_id, quantity) \
+         SELECT $1, component_id, quantity FROM UNNEST($2::UUID[], $3::INT[]) AS c(component_id, quantity)"
+    )
+        .bind(bundle_id)
+        .bind(&component_ids)
+        .bind(&quantities)
+        .execute(&mut *tx)
+        .await;
+
+    match inserted {
+        Ok(_) => {}
+        Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => return HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+
+    // Also recomputes the bundle's stock from its new components
+    let updated = sqlx::query("UPDATE products SET is_bundle = $2 WHERE id = $1")
+        .bind(bundle_id)
+        .bind(!component_ids.is_empty())
+        .execute(&mut *tx)
+        .await;
+
+    match updated {
+        Ok(done) if done.rows_affected() == 0 => return HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Ok(_) => {}
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+
+    if let Err(e) = tx.commit().await {
+        return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+    }
+
+    match components_for_products(&data.db_pool, &[bundle_id]).await {
+        Ok(mut components) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Bundle components updated".to_string(),
+            data: components.remove(&bundle_id).unwrap_or_default(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct BundleComponent {
+    pub product_id: Uuid,
+    pub name: String,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ComponentQuantity {
+    pub product_id: Uuid,
+    pub quantity: i32,
+}
+
+// An empty list turns a bundle back into a regular product
+#[derive(Debug, Deserialize)]
+pub struct BundleUpdate {
+    pub components: Vec<ComponentQuantity>,
+}
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+use std::time::Duration;
+use log::{error, info, warn};
+use sqlx::PgPool;
+use sqlx::postgres::PgListener;
+use uuid::Uuid;
+use crate::cache::CatalogueCache;
+
+// Payload is a product id, or empty for changes that affect every product
+pub const CHANNEL: &str = "catalogue_changed";
+
+pub fn spawn_invalidation_listener(pool: PgPool, cache: Arc<CatalogueCache>) {
+    actix_rt::spawn(async move {
+        loop {
+            let mut listener = match PgListener::connect_with(&pool).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("Failed to open catalogue listener: {}", e);
+                    actix_rt::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            if let Err(e) = listener.listen(CHANNEL).await {
+                error!("Failed to listen on {}: {}", CHANNEL, e);
+                actix_rt::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+            info!("Listening for catalogue changes");
+            // Anything may have changed while nobody was listening
+            cache.invalidate(None).await;
+
+            loop {
+                match listener.try_recv().await {
+                    Ok(Some(notification)) => cache.invalidate(Uuid::parse_str(notification.payload()).ok()).await,
+                    Ok(None) => {
+                        // The listener reconnects on the next call; notifications sent meanwhile are lost
+                        warn!("Catalogue listener lost its connection");
+                        cache.invalidate(None).await;
+                    }
+                    Err(e) => {
+                        error!("Catalogue listener failed: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
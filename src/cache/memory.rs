@@ -0,0 +1,50 @@
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use lru::LruCache;
+use crate::cache::CacheBackend;
+
+pub struct MemoryCache {
+    entries: Mutex<LruCache<String, (Instant, String)>>,
+    ttl: Duration,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self { entries: Mutex::new(LruCache::new(capacity)), ttl }
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemoryCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((stored_at, value)) if stored_at.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.pop(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    async fn put(&self, key: &str, value: String) {
+        self.entries.lock().unwrap().put(key.to_string(), (Instant::now(), value));
+    }
+
+    async fn remove_prefixed(&self, prefixes: &[String]) {
+        let mut entries = self.entries.lock().unwrap();
+        let stale: Vec<String> = entries
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| prefixes.iter().any(|prefix| key.starts_with(prefix.as_str())))
+            .cloned()
+            .collect();
+        for key in stale {
+            entries.pop(&key);
+        }
+    }
+}
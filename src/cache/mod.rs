@@ -0,0 +1,102 @@
+pub mod listener;
+pub mod memory;
+pub mod redis;
+
+use std::env;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use async_trait::async_trait;
+use log::warn;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+use crate::money::Currency;
+
+// Backends log failures and treat them as a miss
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    async fn get(&self, key: &str) -> Option<String>;
+    async fn put(&self, key: &str, value: String);
+    async fn remove_prefixed(&self, prefixes: &[String]);
+}
+
+// The TTL is a backstop for changes that are not notified, such as a sale window opening
+pub struct CatalogueCache {
+    backend: Box<dyn CacheBackend>,
+    // Bumped on every invalidation so data read before it is not stored afterwards
+    generation: AtomicU64,
+}
+
+impl CatalogueCache {
+    pub fn new(backend: Box<dyn CacheBackend>) -> Self {
+        Self { backend, generation: AtomicU64::new(0) }
+    }
+
+    // Take this before reading from the database and pass it to put
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self.backend.get(key).await?;
+        match serde_json::from_str(&value) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn!("Discarding unreadable cache entry {}: {}", key, e);
+                None
+            }
+        }
+    }
+
+    pub async fn put<T: Serialize>(&self, key: &str, generation: u64, value: &T) {
+        if self.generation() != generation {
+            return;
+        }
+        let value = match serde_json::to_string(value) {
+            Ok(value) => value,
+            Err(e) => return warn!("Failed to serialise cache entry {}: {}", key, e),
+        };
+        self.backend.put(key, value).await;
+
+        // An invalidation may have slipped in while we were writing
+        if self.generation() != generation {
+            self.backend.remove_prefixed(&[key.to_string()]).await;
+        }
+    }
+
+    pub async fn invalidate(&self, product_id: Option<Uuid>) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        let prefixes = match product_id {
+            Some(product_id) => vec![LISTING_PREFIX.to_string(), format!("{}{}:", PRODUCT_PREFIX, product_id)],
+            None => vec![String::new()],
+        };
+        self.backend.remove_prefixed(&prefixes).await;
+    }
+}
+
+const LISTING_PREFIX: &str = "listing:";
+const PRODUCT_PREFIX: &str = "product:";
+
+pub fn listing_key(uri: &str, currency: Currency, locale: &str) -> String {
+    format!("{}{}:{}:{}", LISTING_PREFIX, currency.as_str(), locale, uri)
+}
+
+pub fn product_key(product_id: Uuid, uri: &str, currency: Currency, locale: &str) -> String {
+    format!("{}{}:{}:{}:{}", PRODUCT_PREFIX, product_id, currency.as_str(), locale, uri)
+}
+
+pub async fn from_env() -> Arc<CatalogueCache> {
+    let ttl = Duration::from_secs(
+        env::var("CATALOGUE_CACHE_TTL_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(60),
+    );
+
+    let backend: Box<dyn CacheBackend> = match env::var("CATALOGUE_CACHE").unwrap_or_else(|_| "memory".to_string()).as_str() {
+        "redis" => Box::new(self::redis::RedisCache::from_env(ttl).await),
+        _ => {
+            let capacity = env::var("CATALOGUE_CACHE_CAPACITY").ok().and_then(|v| v.parse().ok()).unwrap_or(1000);
+            Box::new(memory::MemoryCache::new(capacity, ttl))
+        }
+    };
+    Arc::new(CatalogueCache::new(backend))
+}
@@ -0,0 +1,82 @@
+use std::env;
+use std::time::Duration;
+use async_trait::async_trait;
+use log::warn;
+use ::redis::AsyncCommands;
+use ::redis::aio::ConnectionManager;
+use crate::cache::CacheBackend;
+
+const KEY_PREFIX: &str = "catalogue:";
+
+pub struct RedisCache {
+    connection: ConnectionManager,
+    ttl: Duration,
+}
+
+impl RedisCache {
+    pub async fn from_env(ttl: Duration) -> Self {
+        let url = env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        let client = ::redis::Client::open(url).expect("REDIS_URL must be a valid Redis URL");
+        let connection = client
+            .get_connection_manager()
+            .await
+            .expect("Failed to connect to Redis");
+        Self { connection, ttl }
+    }
+}
+
+#[async_trait]
+impl CacheBackend for RedisCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let mut connection = self.connection.clone();
+        match connection.get::<_, Option<String>>(format!("{}{}", KEY_PREFIX, key)).await {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("Redis read of {} failed: {}", key, e);
+                None
+            }
+        }
+    }
+
+    async fn put(&self, key: &str, value: String) {
+        let mut connection = self.connection.clone();
+        let result = connection
+            .set_ex::<_, _, ()>(format!("{}{}", KEY_PREFIX, key), value, self.ttl.as_secs().max(1))
+            .await;
+        if let Err(e) = result {
+            warn!("Redis write of {} failed: {}", key, e);
+        }
+    }
+
+    async fn remove_prefixed(&self, prefixes: &[String]) {
+        let mut connection = self.connection.clone();
+        for prefix in prefixes {
+            // Prefixes are fixed key namespaces, free of glob characters
+            let pattern = format!("{}{}*", KEY_PREFIX, prefix);
+            let mut cursor = 0u64;
+            loop {
+                let page = ::redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(&pattern)
+                    .arg("COUNT")
+                    .arg(500)
+                    .query_async::<(u64, Vec<String>)>(&mut connection)
+                    .await;
+                let (next, keys) = match page {
+                    Ok(page) => page,
+                    Err(e) => return warn!("Redis scan of {} failed: {}", pattern, e),
+                };
+                if !keys.is_empty()
+                    && let Err(e) = connection.del::<_, ()>(keys).await
+                {
+                    warn!("Redis delete under {} failed: {}", pattern, e);
+                }
+                if next == 0 {
+                    break;
+                }
+                cursor = next;
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::Utc;
+use uuid::Uuid;
+use crate::cart::models::PricedLine;
+use crate::coupon::models::Coupon;
+use crate::money::{Currency, Money};
+use crate::product::models::{Product, ProductDetails};
+use crate::promotion::models::Promotion;
+
+pub fn product(price: Money, category_id: Option<Uuid>) -> Product {
+    Product {
+        id: Uuid::new_v4(),
+        name: "Test product".to_string(),
+        description: None,
+        price,
+        compare_at_price: None,
+        lowest_price_30d: None,
+        sale_ends_at: None,
+        available: true,
+        stock_quantity: None,
+        is_bundle: false,
+        is_digital: false,
+        rating_average: 0.0,
+        rating_count: 0,
+        attributes: serde_json::json!({}),
+        category_id,
+        version: 1,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+pub fn line_of(product: Product, quantity: i32) -> PricedLine {
+    let unit_price = product.price;
+    PricedLine {
+        product: ProductDetails { product, images: Vec::new(), components: Vec::new(), questions: None },
+        quantity,
+        unit_price,
+        line_total: unit_price.times(quantity as i64).unwrap(),
+    }
+}
+
+pub fn line(price: i64, quantity: i32) -> PricedLine {
+    line_of(product(Money::new(price, Currency::Inr), None), quantity)
+}
+
+pub fn subtotal(lines: &[PricedLine]) -> Money {
+    let currency = lines.first().map_or(Currency::Inr, |line| line.unit_price.currency);
+    lines.iter().try_fold(Money::zero(currency), |sum, line| sum.checked_add(line.line_total)).unwrap()
+}
+
+pub fn coupon(percent_off: Option<i32>, amount_off: Option<i64>) -> Coupon {
+    Coupon {
+        id: Uuid::new_v4(),
+        code: "SAVE".to_string(),
+        kind: "percent".to_string(),
+        percent_off,
+        amount_off,
+        starts_at: None,
+        ends_at: None,
+        max_uses: None,
+        max_uses_per_user: None,
+        min_order_amount: None,
+        product_ids: Vec::new(),
+        category_ids: Vec::new(),
+        active: true,
+        created_at: Utc::now(),
+    }
+}
+
+pub fn promotion(name: &str) -> Promotion {
+    Promotion {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        kind: String::new(),
+        priority: 0,
+        stackable: true,
+        buy_quantity: None,
+        get_quantity: None,
+        get_percent_off: 100,
+        threshold_amount: None,
+        percent_off: None,
+        amount_off: None,
+        product_ids: Vec::new(),
+        category_ids: Vec::new(),
+        starts_at: None,
+        ends_at: None,
+        active: true,
+        created_at: Utc::now(),
+        tiers: Vec::new(),
+    }
+}
@@ -4,10 +4,15 @@ use actix_web::error::PayloadError::Http2Payload;
 use chrono::Month::April;
 use sqlx::PgPool;
 use sqlx::types::Json;
+use serde_json::json;
 use uuid::Uuid;
 use crate::AppState;
 use crate::auth::models::AuthenticatedUser;
-use crate::cart::models::{AddItem, Cart, CartItem};
+use crate::cart::items::{add_item, cart_for_user};
+use crate::cart::models::{AddItem, Cart, CartError, CartItem};
+use crate::cart::pricing::price_cart;
+use crate::locale::models::RequestLocale;
+use crate::pricing::models::{PricingError, RequestCurrency};
 use crate::routes::models::ApiResponse;
 
 pub async fn create_cart(
@@ -53,7 +58,9 @@ pub async fn create_cart(
 
 pub async fn get_cart_items(
     data: web::Data<AppState>,
-    user: AuthenticatedUser
+    user: AuthenticatedUser,
+    currency: RequestCurrency,
+    locale: RequestLocale,
 ) -> impl Responder {
 
     let user_id = match Uuid::parse_str(&user.claims.sub) {
@@ -69,18 +76,18 @@ pub async fn get_cart_items(
     match cart {
         Ok(None) => HttpResponse::Ok().json( ApiResponse { status : "Error".to_string(),msg: "Cart to found".to_string(), data : "no cart" }),
 
-        Ok(Some(cart)) => {
-            let cart_items = sqlx::query_as::<_, CartItem>("SELECT * FROM cart_items WHERE cart_id = $1")
-                .bind(&cart.id)
-                .fetch_all(&data.db_pool)
-                .await;
-            
-            HttpResponse::Ok().json( ApiResponse {
+        Ok(Some(cart)) => match price_cart(&data, cart.id, currency.0, &locale.0).await {
+            Ok(priced) => HttpResponse::Ok().json( ApiResponse {
                 status: "Success".to_string(),
                 msg: "All crat items".to_string(),
-                data: cart_items.unwrap()
-            })
-            
+                data: priced
+            }),
+            Err(e @ (PricingError::Unpriceable { .. } | PricingError::NoRate { .. } | PricingError::Overflow)) => HttpResponse::BadRequest().json( ApiResponse {
+                status: "Error".to_string(),
+                msg: e.to_string(),
+                data: "No data".to_string()
+            }),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
         },
         Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e))
 
@@ -92,25 +99,54 @@ pub async fn add_to_cart(
     user: AuthenticatedUser,
     payload: web::Json<AddItem>
 ) -> impl Responder {
-    
-    let add_item = sqlx::query("Insert Into cart_items(cart_id, product_id, quantity) values ($1, $2, $3)")
-        .bind(&payload.cart_id)
-        .bind(&payload.product_id)
-        .bind(&payload.quantity)
-        .execute(&data.db_pool)
-        .await;
-    
-    match add_item { 
-        Ok(..) => HttpResponse::Ok().json( ApiResponse {
+
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    if payload.quantity < 1 {
+        return HttpResponse::BadRequest().json( ApiResponse {
+            status : "Error".to_string(),
+            msg : "Quantity must be at least 1".to_string(),
+            data: "No data".to_string()
+        });
+    }
+
+    let mut tx = match data.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let cart_id = match cart_for_user(&mut tx, user_id).await {
+        Ok(cart_id) => cart_id,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let quantity = match add_item(&mut tx, cart_id, payload.product_id, payload.quantity).await {
+        Ok(quantity) => quantity,
+        Err(CartError::ProductNotFound) => return HttpResponse::NotFound().json( ApiResponse {
+            status : "Error".to_string(),
+            msg : "Product not found".to_string(),
+            data: "No data".to_string()
+        }),
+        Err(e @ (CartError::ProductUnavailable | CartError::InsufficientStock { .. })) => {
+            return HttpResponse::Conflict().json( ApiResponse {
+                status : "Error".to_string(),
+                msg : e.to_string(),
+                data: "No data".to_string()
+            });
+        }
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    match tx.commit().await {
+        Ok(_) => HttpResponse::Ok().json( ApiResponse {
             status : "Success".to_string(),
             msg : "Item added to the cart successfully".to_string(),
-            data: "No data"
+            data: json!({ "cart_id": cart_id, "product_id": payload.product_id, "quantity": quantity })
         }),
-        Err(_) => HttpResponse::Ok().json( ApiResponse {
-            status : "Error".to_string(),
-            msg : "Error occurred".to_string(),
-            data: "No data".to_string()
-        })
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
     }
 }
 
@@ -165,73 +201,69 @@ pub async fn remove_product_from_cart(
         Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
     };
 
-    // Fetch the user's cart
-    let cart = sqlx::query_as::<_, Cart>("SELECT * FROM shopping_cart WHERE user_id = $1")
-        .bind(&user_id)
-        .fetch_optional(&data.db_pool)
+    if payload.quantity < 1 {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Quantity must be at least 1".to_string(),
+            data: "No data".to_string(),
+        });
+    }
+
+    let mut tx = match data.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    // Lock the line so the quantity cannot change between reading and writing it
+    let item = sqlx::query_as::<_, CartItem>(
+        "SELECT i.* FROM cart_items i JOIN shopping_cart c ON c.id = i.cart_id \
+         WHERE c.user_id = $1 AND i.product_id = $2 FOR UPDATE OF i"
+    )
+        .bind(user_id)
+        .bind(payload.product_id)
+        .fetch_optional(&mut *tx)
         .await;
 
-    match cart {
-        Ok(None) => HttpResponse::Ok().json(ApiResponse {
+    let remaining = match item {
+        Ok(Some(item)) => (item.quantity - payload.quantity).max(0),
+        Ok(None) => return HttpResponse::Ok().json(ApiResponse {
             status: "Error".to_string(),
-            msg: "No cart found".to_string(),
+            msg: "Product not found in cart".to_string(),
             data: "No data".to_string(),
         }),
-        Ok(Some(cart)) => {
-            // Check if the product exists in the cart
-            let product_in_cart = sqlx::query_as::<_, CartItem>(
-                "SELECT * FROM cart_items WHERE cart_id = $1 AND product_id = $2"
-            )
-                .bind(&cart.id)
-                .bind(&payload.product_id)
-                .fetch_optional(&data.db_pool)
-                .await;
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
 
-            match product_in_cart {
-                Ok(Some(mut item)) => {
-                    if payload.quantity == 0 {
-                        // Remove product completely if quantity is 0
-                        let remove_result = sqlx::query("DELETE FROM cart_items WHERE cart_id = $1 AND product_id = $2")
-                            .bind(&cart.id)
-                            .bind(&payload.product_id)
-                            .execute(&data.db_pool)
-                            .await;
-
-                        match remove_result {
-                            Ok(_) => HttpResponse::Ok().json(ApiResponse {
-                                status: "Success".to_string(),
-                                msg: "Product removed from cart".to_string(),
-                                data: "{}".to_string(),
-                            }),
-                            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
-                        }
-                    } else {
-                        // Update product quantity if it's greater than 0
-                        let update_result = sqlx::query("UPDATE cart_items SET quantity = $1 WHERE cart_id = $2 AND product_id = $3")
-                            .bind(&payload.quantity)
-                            .bind(&cart.id)
-                            .bind(&payload.product_id)
-                            .execute(&data.db_pool)
-                            .await;
-
-                        match update_result {
-                            Ok(_) => HttpResponse::Ok().json(ApiResponse {
-                                status: "Success".to_string(),
-                                msg: "Product quantity updated".to_string(),
-                                data: "{}".to_string(),
-                            }),
-                            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
-                        }
-                    }
-                }
-                Ok(None) => HttpResponse::Ok().json(ApiResponse {
-                    status: "Error".to_string(),
-                    msg: "Product not found in cart".to_string(),
-                    data: "No data".to_string(),
-                }),
-                Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
-            }
-        }
+    let result = if remaining == 0 {
+        sqlx::query(
+            "DELETE FROM cart_items i USING shopping_cart c \
+             WHERE c.id = i.cart_id AND c.user_id = $1 AND i.product_id = $2"
+        )
+            .bind(user_id)
+            .bind(payload.product_id)
+            .execute(&mut *tx)
+            .await
+    } else {
+        sqlx::query(
+            "UPDATE cart_items i SET quantity = $3 FROM shopping_cart c \
+             WHERE c.id = i.cart_id AND c.user_id = $1 AND i.product_id = $2"
+        )
+            .bind(user_id)
+            .bind(payload.product_id)
+            .bind(remaining)
+            .execute(&mut *tx)
+            .await
+    };
+    if let Err(e) = result {
+        return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+    }
+
+    match tx.commit().await {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: if remaining == 0 { "Product removed from cart" } else { "Product quantity updated" }.to_string(),
+            data: json!({ "product_id": payload.product_id, "quantity": remaining }),
+        }),
         Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
     }
-}
\ No newline at end of file
+}
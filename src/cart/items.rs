@@ -0,0 +1,53 @@
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+use crate::cart::models::CartError;
+
+pub async fn cart_for_user(tx: &mut Transaction<'_, Postgres>, user_id: Uuid) -> Result<Uuid, sqlx::Error> {
+    // The no-op update makes RETURNING yield the existing cart too
+    sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO shopping_cart (user_id) VALUES ($1) \
+         ON CONFLICT (user_id) DO UPDATE SET user_id = EXCLUDED.user_id \
+         RETURNING id"
+    )
+        .bind(user_id)
+        .fetch_one(&mut **tx)
+        .await
+}
+
+// Stock is only reserved at checkout, but a total that could never be sold is refused
+pub async fn add_item(
+    tx: &mut Transaction<'_, Postgres>,
+    cart_id: Uuid,
+    product_id: Uuid,
+    quantity: i32,
+) -> Result<i32, CartError> {
+    let product = sqlx::query_as::<_, (bool, Option<i32>)>(
+        "SELECT available, stock_quantity - reserved_quantity FROM products WHERE id = $1"
+    )
+        .bind(product_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    let in_stock = match product {
+        Some((true, in_stock)) => in_stock,
+        Some((false, _)) => return Err(CartError::ProductUnavailable),
+        None => return Err(CartError::ProductNotFound),
+    };
+
+    let total = sqlx::query_scalar::<_, i32>(
+        "INSERT INTO cart_items (cart_id, product_id, quantity) VALUES ($1, $2, $3) \
+         ON CONFLICT (cart_id, product_id) DO UPDATE SET quantity = cart_items.quantity + EXCLUDED.quantity \
+         RETURNING quantity"
+    )
+        .bind(cart_id)
+        .bind(product_id)
+        .bind(quantity)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    // The caller drops the transaction, which undoes the upsert
+    if let Some(in_stock) = in_stock.filter(|in_stock| total > *in_stock) {
+        return Err(CartError::InsufficientStock { available: in_stock });
+    }
+    Ok(total)
+}
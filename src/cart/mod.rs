@@ -1,2 +1,6 @@
 pub mod handlers;
-mod models;
\ No newline at end of file
+pub mod items;
+pub mod models;
+pub mod pricing;
+#[cfg(test)]
+pub mod fixtures;
@@ -1,7 +1,12 @@
 
+use std::env;
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 use chrono::NaiveDateTime;
+use crate::coupon::models::CouponStatus;
+use crate::money::{Currency, Money};
+use crate::product::models::ProductDetails;
+use crate::promotion::models::PromotionOutcome;
 
 #[derive(Debug, Serialize, sqlx::FromRow)]
 pub struct Cart {
@@ -20,14 +25,93 @@ pub struct CartItem {
 }
 
 #[derive(Debug, Serialize)]
-pub struct CartWithItems {
-    pub cart: Cart,
-    pub items: Vec<CartItem>,
+pub struct PricedLine {
+    pub product: ProductDetails,
+    pub quantity: i32,
+    // Sale prices included
+    pub unit_price: Money,
+    pub line_total: Money,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Discount {
+    pub label: String,
+    pub amount: Money,
+    // Waives shipping rather than reducing the subtotal, so tax is unaffected
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub on_shipping: bool,
+}
+
+// total is subtotal - discount_total + tax + shipping
+#[derive(Debug, Serialize)]
+pub struct PricedCart {
+    pub cart_id: Uuid,
+    pub currency: Currency,
+    pub lines: Vec<PricedLine>,
+    pub subtotal: Money,
+    pub discounts: Vec<Discount>,
+    pub discount_total: Money,
+    pub tax: Money,
+    pub shipping: Money,
+    pub total: Money,
+    pub promotions: Vec<PromotionOutcome>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coupon: Option<CouponStatus>,
+    // The coupon checkout redeems and the discount it gives
+    #[serde(skip)]
+    pub coupon_redemption: Option<(Uuid, Money)>,
+}
+
+// Amounts are in minor units of the base currency
+#[derive(Debug, Clone)]
+pub struct CartCharges {
+    // Basis points: 1800 is 18%
+    pub tax_rate_bps: i64,
+    pub shipping_fee: i64,
+    pub free_shipping_over: Option<i64>,
 }
 
+impl CartCharges {
+    pub fn from_env() -> Self {
+        let read = |name: &str| env::var(name).ok().and_then(|v| v.parse::<i64>().ok()).filter(|v| *v >= 0);
+        CartCharges {
+            tax_rate_bps: read("TAX_RATE_BPS").unwrap_or(0),
+            shipping_fee: read("SHIPPING_FEE").unwrap_or(0),
+            free_shipping_over: read("FREE_SHIPPING_OVER"),
+        }
+    }
+}
+
+// A cart_id sent by older clients is ignored
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AddItem{
-    pub cart_id: Uuid,
     pub product_id: Uuid,
     pub quantity: i32,
 }
+
+#[derive(Debug)]
+pub enum CartError {
+    ProductNotFound,
+    ProductUnavailable,
+    InsufficientStock { available: i32 },
+    Db(sqlx::Error),
+}
+
+impl std::fmt::Display for CartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CartError::ProductNotFound => write!(f, "Product not found"),
+            CartError::ProductUnavailable => write!(f, "Product is not available"),
+            CartError::InsufficientStock { available } => {
+                write!(f, "Not enough stock for the requested quantity ({} available)", available)
+            }
+            CartError::Db(e) => write!(f, "DB error: {}", e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for CartError {
+    fn from(e: sqlx::Error) -> Self {
+        CartError::Db(e)
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use crate::AppState;
+use crate::cart::models::{CartCharges, Discount, PricedCart, PricedLine};
+use crate::coupon::engine::{coupon_label, coupon_uses, evaluate, CouponEffect, CouponUses};
+use crate::coupon::models::{Coupon, CouponStatus};
+use crate::money::{Currency, Money};
+use crate::pricing::models::{BaseRate, PricingError};
+use crate::pricing::rates::base_rate;
+use crate::product::handlers::product_details;
+use crate::product::models::Product;
+use crate::promotion::engine::{active_promotions, apply_promotions};
+use crate::promotion::models::Promotion;
+
+// With the coupon saved on the cart, as checkout charges it
+pub async fn price_cart(
+    data: &AppState,
+    cart_id: Uuid,
+    currency: Currency,
+    locale: &str,
+) -> Result<PricedCart, PricingError> {
+    let (user_id, coupon_id) = sqlx::query_as::<_, (Uuid, Option<Uuid>)>(
+        "SELECT user_id, coupon_id FROM shopping_cart WHERE id = $1"
+    )
+        .bind(cart_id)
+        .fetch_one(&data.db_pool)
+        .await?;
+
+    let coupon = match coupon_id {
+        Some(coupon_id) => sqlx::query_as::<_, Coupon>("SELECT * FROM coupons WHERE id = $1")
+            .bind(coupon_id)
+            .fetch_optional(&data.db_pool)
+            .await?,
+        None => None,
+    };
+
+    price_cart_with(data, cart_id, user_id, coupon.as_ref(), currency, locale).await
+}
+
+pub async fn price_cart_with(
+    data: &AppState,
+    cart_id: Uuid,
+    user_id: Uuid,
+    coupon: Option<&Coupon>,
+    currency: Currency,
+    locale: &str,
+) -> Result<PricedCart, PricingError> {
+    let items = sqlx::query_as::<_, (Uuid, i32)>(
+        "SELECT product_id, quantity FROM cart_items WHERE cart_id = $1 ORDER BY added_at, id"
+    )
+        .bind(cart_id)
+        .fetch_all(&data.db_pool)
+        .await?;
+
+    let product_ids: Vec<Uuid> = items.iter().map(|(product_id, _)| *product_id).collect();
+    let products = sqlx::query_as::<_, Product>("SELECT * FROM products WHERE id = ANY($1)")
+        .bind(&product_ids)
+        .fetch_all(&data.db_pool)
+        .await?;
+
+    let mut details: HashMap<Uuid, _> = product_details(data, products, currency, locale)
+        .await?
+        .into_iter()
+        .map(|details| (details.product.id, details))
+        .collect();
+
+    let mut lines = Vec::with_capacity(items.len());
+    for (product_id, quantity) in items {
+        let Some(product) = details.remove(&product_id) else { continue };
+        let unit_price = product.product.price;
+        lines.push(PricedLine {
+            line_total: unit_price.times(quantity as i64)?,
+            product,
+            quantity,
+            unit_price,
+        });
+    }
+
+    let promotions = active_promotions(&data.db_pool).await?;
+    let coupon = match coupon {
+        Some(coupon) => Some((coupon, coupon_uses(&data.db_pool, coupon.id, user_id).await?)),
+        None => None,
+    };
+    let terms = CartTerms {
+        rate: base_rate(&data.db_pool, data.base_currency, currency).await?,
+        charges: &data.cart_charges,
+        promotions: &promotions,
+        coupon,
+        now: Utc::now(),
+    };
+    price_lines(cart_id, lines, &terms)
+}
+
+pub struct CartTerms<'a> {
+    pub rate: BaseRate,
+    pub charges: &'a CartCharges,
+    pub promotions: &'a [Promotion],
+    pub coupon: Option<(&'a Coupon, CouponUses)>,
+    pub now: DateTime<Utc>,
+}
+
+// Shipping is free over the threshold on the discounted subtotal, and for digital-only carts
+pub fn price_lines(cart_id: Uuid, lines: Vec<PricedLine>, terms: &CartTerms) -> Result<PricedCart, PricingError> {
+    let currency = terms.rate.currency;
+    let subtotal = lines.iter().try_fold(Money::zero(currency), |sum, line| sum.checked_add(line.line_total))?;
+    let run = apply_promotions(&terms.rate, terms.promotions, &lines, subtotal)?;
+    let mut discounts = run.discounts;
+    let promoted = discounts.iter().try_fold(subtotal, |sum, d| sum.checked_sub(d.amount))?;
+
+    let mut coupon_status = None;
+    let mut coupon_effect = None;
+    if let Some((coupon, uses)) = terms.coupon {
+        let (applied, reason) = match evaluate(coupon, uses, &terms.rate, &lines, subtotal, terms.now)? {
+            Ok(effect) => {
+                coupon_effect = Some((coupon, effect));
+                (true, None)
+            }
+            Err(rejection) => (false, Some(rejection.to_string())),
+        };
+        coupon_status = Some(CouponStatus { code: coupon.code.clone(), applied, reason });
+    }
+
+    let mut coupon_redemption = None;
+    if let Some((coupon, CouponEffect::Reduce(amount))) = coupon_effect {
+        let amount = Money::new(amount.minor_units.min(promoted.minor_units), currency);
+        discounts.push(Discount { label: coupon_label(coupon, amount), amount, on_shipping: false });
+        coupon_redemption = Some((coupon.id, amount));
+    }
+    let discounted = discounts.iter().try_fold(subtotal, |sum, d| sum.checked_sub(d.amount))?;
+
+    let charges = terms.charges;
+    let ships = lines.iter().any(|line| !line.product.product.is_digital);
+    let shipping = if !ships || charges.shipping_fee == 0 {
+        Money::zero(currency)
+    } else {
+        let threshold = charges.free_shipping_over.map(|over| terms.rate.convert(over)).transpose()?;
+        match threshold {
+            Some(threshold) if discounted.minor_units >= threshold.minor_units => Money::zero(currency),
+            _ => terms.rate.convert(charges.shipping_fee)?,
+        }
+    };
+
+    // A promotion waiving shipping leaves nothing for a free-shipping coupon to take off
+    let mut shipping_due = shipping;
+    if let Some(label) = run.free_shipping.filter(|_| shipping.minor_units > 0) {
+        discounts.push(Discount { label, amount: shipping, on_shipping: true });
+        shipping_due = Money::zero(currency);
+    }
+    if let Some((coupon, CouponEffect::FreeShipping)) = coupon_effect {
+        if shipping_due.minor_units > 0 {
+            discounts.push(Discount { label: coupon_label(coupon, shipping_due), amount: shipping_due, on_shipping: true });
+        }
+        coupon_redemption = Some((coupon.id, shipping_due));
+    }
+
+    let tax = discounted.share(charges.tax_rate_bps, 10_000)?;
+    let discount_total = discounts.iter().try_fold(Money::zero(currency), |sum, d| sum.checked_add(d.amount))?;
+    let total = subtotal.checked_sub(discount_total)?.checked_add(tax)?.checked_add(shipping)?;
+
+    Ok(PricedCart {
+        cart_id,
+        currency,
+        lines,
+        subtotal,
+        discounts,
+        discount_total,
+        tax,
+        shipping,
+        total,
+        promotions: run.outcomes,
+        coupon: coupon_status,
+        coupon_redemption,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cart::fixtures::{coupon, line, line_of, product, promotion};
+    use super::*;
+
+    const CHARGES: CartCharges = CartCharges { tax_rate_bps: 1_800, shipping_fee: 500, free_shipping_over: Some(5_000) };
+
+    fn terms<'a>(promotions: &'a [Promotion], coupon: Option<&'a Coupon>) -> CartTerms<'a> {
+        CartTerms {
+            rate: BaseRate::identity(Currency::Inr),
+            charges: &CHARGES,
+            promotions,
+            coupon: coupon.map(|coupon| (coupon, CouponUses::default())),
+            now: Utc::now(),
+        }
+    }
+
+    fn spend(name: &str, threshold: i64, percent_off: Option<i32>, amount_off: Option<i64>) -> Promotion {
+        Promotion { threshold_amount: Some(threshold), percent_off, amount_off, ..promotion(name) }
+    }
+
+    fn minor(amount: Money) -> i64 {
+        amount.minor_units
+    }
+
+    #[test]
+    fn total_adds_tax_and_shipping_to_the_subtotal() {
+        let cart = price_lines(Uuid::new_v4(), vec![line(1_000, 2)], &terms(&[], None)).unwrap();
+        assert_eq!(minor(cart.subtotal), 2_000);
+        assert_eq!(minor(cart.tax), 360);
+        assert_eq!(minor(cart.shipping), 500);
+        assert_eq!(minor(cart.total), 2_860);
+    }
+
+    #[test]
+    fn subtotal_past_the_money_range_is_an_error() {
+        let lines = vec![line(i64::MAX / 2 + 1, 1), line(i64::MAX / 2 + 1, 1)];
+        let priced = price_lines(Uuid::new_v4(), lines, &terms(&[], None));
+        assert!(matches!(priced, Err(PricingError::Overflow)));
+    }
+
+    #[test]
+    fn free_shipping_threshold_applies_to_the_discounted_subtotal() {
+        let cart = price_lines(Uuid::new_v4(), vec![line(5_000, 1)], &terms(&[], None)).unwrap();
+        assert_eq!(minor(cart.shipping), 0);
+
+        let promotions = [spend("10% off", 1_000, Some(10), None)];
+        let cart = price_lines(Uuid::new_v4(), vec![line(5_000, 1)], &terms(&promotions, None)).unwrap();
+        assert_eq!(minor(cart.discount_total), 500);
+        assert_eq!(minor(cart.shipping), 500);
+        assert_eq!(minor(cart.tax), 810);
+        assert_eq!(minor(cart.total), 5_810);
+    }
+
+    #[test]
+    fn digital_only_cart_ships_free() {
+        let download = Product { is_digital: true, ..product(Money::new(1_000, Currency::Inr), None) };
+        let cart = price_lines(Uuid::new_v4(), vec![line_of(download, 1)], &terms(&[], None)).unwrap();
+        assert_eq!(minor(cart.shipping), 0);
+    }
+
+    #[test]
+    fn coupon_takes_at_most_what_promotions_left() {
+        let promotions = [spend("700 off", 100, None, Some(700))];
+        let fixed = coupon(None, Some(500));
+        let cart = price_lines(Uuid::new_v4(), vec![line(1_000, 1)], &terms(&promotions, Some(&fixed))).unwrap();
+        assert_eq!(cart.discounts.iter().map(|d| minor(d.amount)).collect::<Vec<_>>(), vec![700, 300]);
+        assert_eq!(minor(cart.tax), 0);
+        assert_eq!(cart.coupon_redemption, Some((fixed.id, Money::new(300, Currency::Inr))));
+        assert_eq!(minor(cart.total), 500);
+    }
+
+    #[test]
+    fn free_shipping_coupon_discounts_the_shipping_line_only() {
+        let free = coupon(None, None);
+        let cart = price_lines(Uuid::new_v4(), vec![line(1_000, 1)], &terms(&[], Some(&free))).unwrap();
+        assert!(cart.discounts[0].on_shipping);
+        assert_eq!(minor(cart.discount_total), 500);
+        assert_eq!(minor(cart.tax), 180);
+        assert_eq!(minor(cart.total), 1_180);
+        assert_eq!(cart.coupon_redemption, Some((free.id, Money::new(500, Currency::Inr))));
+    }
+
+    #[test]
+    fn free_shipping_promotion_leaves_nothing_for_a_free_shipping_coupon() {
+        let promotions = [spend("Ship free", 100, None, None)];
+        let free = coupon(None, None);
+        let cart = price_lines(Uuid::new_v4(), vec![line(1_000, 1)], &terms(&promotions, Some(&free))).unwrap();
+        assert_eq!(cart.discounts.len(), 1);
+        assert_eq!(cart.discounts[0].label, "Ship free");
+        assert_eq!(minor(cart.total), 1_180);
+        assert_eq!(cart.coupon_redemption, Some((free.id, Money::zero(Currency::Inr))));
+    }
+
+    #[test]
+    fn rejected_coupon_is_reported_and_not_redeemed() {
+        let expired = Coupon { ends_at: Some(Utc::now()), ..coupon(Some(10), None) };
+        let cart = price_lines(Uuid::new_v4(), vec![line(1_000, 1)], &terms(&[], Some(&expired))).unwrap();
+        let status = cart.coupon.unwrap();
+        assert!(!status.applied);
+        assert_eq!(status.reason.as_deref(), Some("This code has expired"));
+        assert!(cart.discounts.is_empty());
+        assert!(cart.coupon_redemption.is_none());
+    }
+}
@@ -0,0 +1,145 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde_json::json;
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::AdminUser;
+use crate::cart::items::cart_for_user;
+use crate::cart::pricing::price_cart;
+use crate::cart_recovery::models::{RecoveryStats, RecoveryStatsQuery};
+use crate::cart_recovery::reminders::restore_items;
+use crate::locale::models::RequestLocale;
+use crate::money::Money;
+use crate::pricing::models::{PricingError, RequestCurrency};
+use crate::routes::models::ApiResponse;
+
+fn unknown_link() -> HttpResponse {
+    HttpResponse::NotFound().json(ApiResponse {
+        status: "Error".to_string(),
+        msg: "This link is not valid".to_string(),
+        data: "No Data".to_string(),
+    })
+}
+
+// No login needed: the link identifies the owner and can only add the reminded items back
+pub async fn restore_cart(
+    data: web::Data<AppState>,
+    token: web::Path<String>,
+    currency: RequestCurrency,
+    locale: RequestLocale,
+) -> impl Responder {
+    let reminder = sqlx::query_as::<_, (Uuid, Uuid)>("SELECT id, user_id FROM cart_reminders WHERE token = $1")
+        .bind(token.as_str())
+        .fetch_optional(&data.db_pool)
+        .await;
+    let (reminder_id, user_id) = match reminder {
+        Ok(Some(reminder)) => reminder,
+        Ok(None) => return unknown_link(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let mut tx = match data.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+    let cart_id = match cart_for_user(&mut tx, user_id).await {
+        Ok(cart_id) => cart_id,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+    let restored = match restore_items(&mut tx, reminder_id, cart_id).await {
+        Ok(restored) => restored,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+    if let Err(e) = tx.commit().await {
+        return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+    }
+
+    match price_cart(&data, cart_id, currency.0, &locale.0).await {
+        Ok(priced) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: format!("{} items restored to your cart", restored.len()),
+            data: json!({ "restored": restored, "cart": priced }),
+        }),
+        Err(e @ (PricingError::Unpriceable { .. } | PricingError::NoRate { .. } | PricingError::Overflow)) => {
+            HttpResponse::BadRequest().json(ApiResponse {
+                status: "Error".to_string(),
+                msg: e.to_string(),
+                data: "No Data".to_string(),
+            })
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn unsubscribe_reminders(
+    data: web::Data<AppState>,
+    token: web::Path<String>,
+) -> impl Responder {
+    let result = sqlx::query(
+        "UPDATE users u SET cart_reminders = FALSE FROM cart_reminders r \
+         WHERE r.user_id = u.id AND r.token = $1"
+    )
+        .bind(token.as_str())
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(result) if result.rows_affected() > 0 => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "You will not receive cart reminders any more".to_string(),
+            data: "{}".to_string(),
+        }),
+        Ok(_) => unknown_link(),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn get_recovery_stats(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    query: web::Query<RecoveryStatsQuery>,
+) -> impl Responder {
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+
+    let counts = sqlx::query_as::<_, (i64, i64, i64)>(
+        "SELECT COUNT(*), COUNT(restored_at), COUNT(order_id) FROM cart_reminders \
+         WHERE sent_at > now() - make_interval(days => $1)"
+    )
+        .bind(days)
+        .fetch_one(&data.db_pool)
+        .await;
+    let (reminders_sent, restored, recovered) = match counts {
+        Ok(counts) => counts,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let revenue = sqlx::query(
+        "SELECT SUM(o.total_amount)::BIGINT AS total_amount, o.currency FROM cart_reminders r \
+         JOIN orders o ON o.id = r.order_id \
+         WHERE r.sent_at > now() - make_interval(days => $1) \
+         GROUP BY o.currency ORDER BY o.currency"
+    )
+        .bind(days)
+        .fetch_all(&data.db_pool)
+        .await
+        .and_then(|rows| {
+            rows.iter()
+                .map(|row| Money::from_columns(row, "total_amount", "currency"))
+                .collect::<Result<Vec<_>, _>>()
+        });
+
+    match revenue {
+        Ok(recovered_revenue) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Abandoned cart recovery".to_string(),
+            data: RecoveryStats {
+                days,
+                reminders_sent,
+                restored,
+                recovered,
+                conversion_rate: if reminders_sent > 0 { recovered as f64 / reminders_sent as f64 } else { 0.0 },
+                recovered_revenue,
+            },
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
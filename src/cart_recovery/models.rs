@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use crate::money::Money;
+
+#[derive(Debug, Deserialize)]
+pub struct RecoveryStatsQuery {
+    pub days: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryStats {
+    pub days: i32,
+    pub reminders_sent: i64,
+    pub restored: i64,
+    // Followed by a paid order within the attribution window
+    pub recovered: i64,
+    pub conversion_rate: f64,
+    // Per currency
+    pub recovered_revenue: Vec<Money>,
+}
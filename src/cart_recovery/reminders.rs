@@ -0,0 +1,200 @@
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use log::{error, info, warn};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+use crate::mailer::{app_base_url, Email, Mailer};
+
+fn env_or(name: &str, default: i32) -> i32 {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.parse::<i32>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(default)
+}
+
+pub fn recovery_window_days() -> i32 {
+    env_or("CART_RECOVERY_WINDOW_DAYS", 7)
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct IdleCart {
+    cart_id: Uuid,
+    user_id: Uuid,
+    email: String,
+}
+
+// Claiming via reminder_sent_at gives each idle spell one reminder; a failed send releases the claim
+pub async fn send_cart_reminders(pool: &PgPool, mailer: &dyn Mailer, idle_hours: i32) -> Result<usize, sqlx::Error> {
+    let idle = sqlx::query_as::<_, IdleCart>(
+        "UPDATE shopping_cart c SET reminder_sent_at = now() FROM users u \
+         WHERE u.id = c.user_id AND u.email IS NOT NULL AND u.cart_reminders \
+           AND c.updated_at < now() - make_interval(hours => $1) \
+           AND (c.reminder_sent_at IS NULL OR c.reminder_sent_at < c.updated_at) \
+           AND EXISTS (SELECT 1 FROM cart_items i WHERE i.cart_id = c.id) \
+           AND NOT EXISTS (SELECT 1 FROM orders o WHERE o.user_id = c.user_id \
+                           AND o.status IN ('pending', 'paid') AND o.created_at >= c.updated_at) \
+         RETURNING c.id AS cart_id, c.user_id, u.email"
+    )
+        .bind(idle_hours)
+        .fetch_all(pool)
+        .await?;
+
+    let base_url = app_base_url();
+    let mut sent = 0;
+    for cart in idle {
+        let mut tx = pool.begin().await?;
+        let (reminder_id, token) = record_reminder(&mut tx, &cart).await?;
+        let items = sqlx::query_as::<_, (String, i32)>(
+            "SELECT p.name, i.quantity FROM cart_reminder_items i JOIN products p ON p.id = i.product_id \
+             WHERE i.reminder_id = $1 ORDER BY p.name"
+        )
+            .bind(reminder_id)
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        let lines: Vec<String> = items.iter().map(|(name, quantity)| format!("  {} x {}", quantity, name)).collect();
+        let email = Email {
+            to: cart.email.clone(),
+            subject: "You left something in your cart".to_string(),
+            body: format!(
+                "Your cart is waiting for you:\n\n{}\n\nPick up where you left off: {}/cart/restore/{}\n\n\
+                 To stop cart reminders: {}/cart/reminders/unsubscribe/{}\n",
+                lines.join("\n"), base_url, token, base_url, token
+            ),
+        };
+
+        match mailer.send(&email).await {
+            Ok(()) => sent += 1,
+            Err(e) => {
+                warn!("Failed to send cart reminder for cart {}: {}", cart.cart_id, e);
+                sqlx::query("DELETE FROM cart_reminders WHERE id = $1")
+                    .bind(reminder_id)
+                    .execute(pool)
+                    .await?;
+                sqlx::query("UPDATE shopping_cart SET reminder_sent_at = NULL WHERE id = $1")
+                    .bind(cart.cart_id)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+    }
+    Ok(sent)
+}
+
+// The copy of the items lets the restore link outlive the cart
+async fn record_reminder(tx: &mut Transaction<'_, Postgres>, cart: &IdleCart) -> Result<(Uuid, String), sqlx::Error> {
+    let token = Uuid::new_v4().simple().to_string();
+    let reminder_id = sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO cart_reminders (cart_id, user_id, token) VALUES ($1, $2, $3) RETURNING id"
+    )
+        .bind(cart.cart_id)
+        .bind(cart.user_id)
+        .bind(&token)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO cart_reminder_items (reminder_id, product_id, quantity) \
+         SELECT $1, product_id, quantity FROM cart_items WHERE cart_id = $2"
+    )
+        .bind(reminder_id)
+        .bind(cart.cart_id)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok((reminder_id, token))
+}
+
+// Capped at sellable stock; items already in the cart are left as they are
+pub async fn restore_items(
+    tx: &mut Transaction<'_, Postgres>,
+    reminder_id: Uuid,
+    cart_id: Uuid,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query("UPDATE cart_reminders SET restored_at = COALESCE(restored_at, now()) WHERE id = $1")
+        .bind(reminder_id)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO cart_items (cart_id, product_id, quantity) \
+         SELECT $1, i.product_id, LEAST(i.quantity, p.stock_quantity - p.reserved_quantity) \
+         FROM cart_reminder_items i JOIN products p ON p.id = i.product_id \
+         WHERE i.reminder_id = $2 AND p.available AND COALESCE(p.stock_quantity - p.reserved_quantity > 0, TRUE) \
+         ON CONFLICT (cart_id, product_id) DO NOTHING \
+         RETURNING product_id"
+    )
+        .bind(cart_id)
+        .bind(reminder_id)
+        .fetch_all(&mut **tx)
+        .await
+}
+
+pub async fn record_recovery(tx: &mut Transaction<'_, Postgres>, order_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE cart_reminders SET order_id = $1, recovered_at = now() \
+         WHERE id = ( \
+            SELECT r.id FROM cart_reminders r JOIN orders o ON o.user_id = r.user_id \
+            WHERE o.id = $1 AND r.sent_at > now() - make_interval(days => $2) \
+            ORDER BY r.sent_at DESC LIMIT 1) \
+           AND order_id IS NULL"
+    )
+        .bind(order_id)
+        .bind(recovery_window_days())
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+// Reminders keep their copy of the items
+pub async fn expire_carts(pool: &PgPool, expire_days: i32) -> Result<usize, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let expired = sqlx::query_scalar::<_, Uuid>(
+        "SELECT id FROM shopping_cart WHERE updated_at < now() - make_interval(days => $1) FOR UPDATE SKIP LOCKED"
+    )
+        .bind(expire_days)
+        .fetch_all(&mut *tx)
+        .await?;
+    if expired.is_empty() {
+        return Ok(0);
+    }
+
+    sqlx::query("DELETE FROM cart_items WHERE cart_id = ANY($1)")
+        .bind(&expired)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM shopping_cart WHERE id = ANY($1)")
+        .bind(&expired)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(expired.len())
+}
+
+pub fn spawn_recovery_job(pool: PgPool, mailer: Arc<dyn Mailer>) {
+    let period = env_or("CART_RECOVERY_CHECK_SECS", 900) as u64;
+    let idle_hours = env_or("ABANDONED_CART_IDLE_HOURS", 24);
+    let expire_days = env_or("CART_EXPIRY_DAYS", 90);
+
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(Duration::from_secs(period));
+        loop {
+            interval.tick().await;
+            match send_cart_reminders(&pool, mailer.as_ref(), idle_hours).await {
+                Ok(0) => {}
+                Ok(sent) => info!("Sent {} abandoned cart reminders", sent),
+                Err(e) => error!("Failed to send abandoned cart reminders: {}", e),
+            }
+            match expire_carts(&pool, expire_days).await {
+                Ok(0) => {}
+                Ok(expired) => info!("Expired {} abandoned carts", expired),
+                Err(e) => error!("Failed to expire abandoned carts: {}", e),
+            }
+        }
+    });
+}
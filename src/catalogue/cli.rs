@@ -0,0 +1,41 @@
+use std::io;
+use sqlx::PgPool;
+use crate::catalogue::export::{encode, rows};
+use crate::catalogue::import::import;
+use crate::catalogue::models::Format;
+
+const USAGE: &str = "usage: e-commerceapi import <file.csv|file.json> [--dry-run]\n       e-commerceapi export <file.csv|file.json>";
+
+fn format_for(path: &str) -> Format {
+    if path.ends_with(".json") { Format::Json } else { Format::Csv }
+}
+
+pub async fn run(pool: &PgPool, args: &[String]) -> io::Result<()> {
+    match args {
+        [command, path, flags @ ..] if command == "import" => {
+            let dry_run = flags.iter().any(|f| f == "--dry-run");
+            let bytes = std::fs::read(path)?;
+
+            let report = import(pool, &bytes, format_for(path), dry_run, None)
+                .await
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+
+            if report.invalid > 0 {
+                return Err(io::Error::other(format!("{} invalid rows, nothing was imported", report.invalid)));
+            }
+            Ok(())
+        }
+        [command, path] if command == "export" => {
+            let (rows, skipped) = rows(pool).await.map_err(|e| io::Error::other(e.to_string()))?;
+            let bytes = encode(&rows, format_for(path)).map_err(io::Error::other)?;
+            std::fs::write(path, bytes)?;
+            println!("Exported {} products to {}", rows.len(), path);
+            if skipped > 0 {
+                println!("Skipped {} products without a SKU; give them one to include them", skipped);
+            }
+            Ok(())
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, USAGE)),
+    }
+}
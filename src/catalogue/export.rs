@@ -0,0 +1,50 @@
+use sqlx::PgPool;
+use crate::catalogue::models::{CatalogueRow, Format};
+use crate::money::Currency;
+
+// import matches rows by SKU, so products without one are left out and counted
+pub async fn rows(pool: &PgPool) -> Result<(Vec<CatalogueRow>, i64), sqlx::Error> {
+    let products = sqlx::query_as::<_, (String, String, Option<String>, i64, String, bool, Option<i32>)>(
+        "SELECT sku, name, description, price, currency, available, stock_quantity \
+         FROM products WHERE COALESCE(sku, '') <> '' ORDER BY sku"
+    )
+        .fetch_all(pool)
+        .await?;
+
+    let skipped = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM products WHERE COALESCE(sku, '') = ''")
+        .fetch_one(pool)
+        .await?;
+
+    let rows = products
+        .into_iter()
+        .map(|(sku, name, description, price, currency, available, stock_quantity)| {
+            let currency = currency.parse::<Currency>().map_err(|e| sqlx::Error::ColumnDecode {
+                index: "currency".to_string(),
+                source: Box::new(e),
+            })?;
+            Ok(CatalogueRow {
+                sku,
+                name,
+                description,
+                price,
+                currency,
+                available,
+                stock_quantity,
+            })
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()?;
+    Ok((rows, skipped))
+}
+
+pub fn encode(rows: &[CatalogueRow], format: Format) -> Result<Vec<u8>, String> {
+    match format {
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            for row in rows {
+                writer.serialize(row).map_err(|e| e.to_string())?;
+            }
+            writer.into_inner().map_err(|e| e.to_string())
+        }
+        Format::Json => serde_json::to_vec_pretty(rows).map_err(|e| e.to_string()),
+    }
+}
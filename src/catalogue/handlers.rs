@@ -0,0 +1,72 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::AdminUser;
+use crate::catalogue::export::{encode, rows};
+use crate::catalogue::import::{import, ImportError};
+use crate::catalogue::models::{ExportQuery, Format, ImportQuery};
+use crate::routes::models::ApiResponse;
+
+pub async fn import_catalogue(
+    data: web::Data<AppState>,
+    admin: AdminUser,
+    req: HttpRequest,
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+) -> impl Responder {
+    let format = query.format.unwrap_or_else(|| {
+        match req.headers().get("Content-Type").and_then(|v| v.to_str().ok()) {
+            Some(content_type) if content_type.starts_with("text/csv") => Format::Csv,
+            _ => Format::Json,
+        }
+    });
+
+    let actor_id = Uuid::parse_str(&admin.claims.sub).ok();
+
+    match import(&data.db_pool, &body, format, query.dry_run, actor_id).await {
+        Ok(report) if report.invalid > 0 => HttpResponse::UnprocessableEntity().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Catalogue has invalid rows, nothing was imported".to_string(),
+            data: report,
+        }),
+        Ok(report) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: if report.dry_run { "Dry run completed".to_string() } else { "Catalogue imported".to_string() },
+            data: report,
+        }),
+        Err(e @ ImportError::Parse(_)) => HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: e.to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn export_catalogue(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    query: web::Query<ExportQuery>,
+) -> impl Responder {
+    let format = query.format.unwrap_or(Format::Csv);
+
+    let (rows, skipped) = match rows(&data.db_pool).await {
+        Ok(exported) => exported,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    match encode(&rows, format) {
+        Ok(bytes) => {
+            let (content_type, filename) = match format {
+                Format::Csv => ("text/csv", "products.csv"),
+                Format::Json => ("application/json", "products.json"),
+            };
+            HttpResponse::Ok()
+                .content_type(content_type)
+                .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", filename)))
+                .insert_header(("X-Skipped-Without-Sku", skipped.to_string()))
+                .body(bytes)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::product::history::begin_as;
+use crate::catalogue::models::{CatalogueRow, Format, ImportReport, RowReport, RowStatus};
+
+#[derive(Debug)]
+pub enum ImportError {
+    Parse(String),
+    Db(sqlx::Error),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Parse(e) => write!(f, "Could not parse catalogue: {}", e),
+            ImportError::Db(e) => write!(f, "DB error: {}", e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for ImportError {
+    fn from(e: sqlx::Error) -> Self {
+        ImportError::Db(e)
+    }
+}
+
+// Decode errors are kept per row instead of failing the whole file
+pub fn parse(bytes: &[u8], format: Format) -> Result<Vec<Result<CatalogueRow, String>>, ImportError> {
+    match format {
+        Format::Csv => {
+            let mut reader = csv::Reader::from_reader(bytes);
+            reader.headers().map_err(|e| ImportError::Parse(e.to_string()))?;
+            Ok(reader
+                .deserialize::<CatalogueRow>()
+                .map(|row| row.map_err(|e| e.to_string()))
+                .collect())
+        }
+        Format::Json => {
+            let values: Vec<serde_json::Value> =
+                serde_json::from_slice(bytes).map_err(|e| ImportError::Parse(e.to_string()))?;
+            Ok(values
+                .into_iter()
+                .map(|value| serde_json::from_value::<CatalogueRow>(value).map_err(|e| e.to_string()))
+                .collect())
+        }
+    }
+}
+
+pub fn validate(row: &CatalogueRow) -> Vec<String> {
+    let mut errors = vec![];
+    if row.sku.trim().is_empty() {
+        errors.push("sku is required".to_string());
+    }
+    if row.name.trim().is_empty() {
+        errors.push("name is required".to_string());
+    }
+    if row.price < 0 {
+        errors.push("price cannot be negative".to_string());
+    }
+    if row.stock_quantity.is_some_and(|stock| stock < 0) {
+        errors.push("stock_quantity cannot be negative".to_string());
+    }
+    errors
+}
+
+// A dry run goes through the same upserts and rolls back, so the counts are exact
+pub async fn import(
+    pool: &PgPool,
+    bytes: &[u8],
+    format: Format,
+    dry_run: bool,
+    actor_id: Option<Uuid>,
+) -> Result<ImportReport, ImportError> {
+    let parsed = parse(bytes, format)?;
+
+    let mut seen = HashSet::new();
+    let mut rows = Vec::with_capacity(parsed.len());
+    let mut reports = Vec::with_capacity(parsed.len());
+
+    for (index, row) in parsed.into_iter().enumerate() {
+        let (sku, errors) = match &row {
+            Ok(row) => {
+                let mut errors = validate(row);
+                if !seen.insert(row.sku.trim().to_string()) {
+                    errors.push("sku appears more than once in this file".to_string());
+                }
+                (Some(row.sku.clone()), errors)
+            }
+            Err(e) => (None, vec![e.clone()]),
+        };
+
+        reports.push(RowReport {
+            row: index + 1,
+            sku,
+            status: if errors.is_empty() { RowStatus::Created } else { RowStatus::Invalid },
+            errors,
+        });
+        rows.push(row.ok());
+    }
+
+    let invalid = reports.iter().filter(|r| r.status == RowStatus::Invalid).count();
+    if invalid > 0 {
+        return Ok(ImportReport { dry_run, applied: false, created: 0, updated: 0, invalid, rows: reports });
+    }
+
+    let mut tx = begin_as(pool, actor_id).await?;
+
+    for (row, report) in rows.iter().flatten().zip(reports.iter_mut()) {
+        let inserted = sqlx::query_scalar::<_, bool>(
+            r#"
+            INSERT INTO products (sku, name, description, price, currency, available, stock_quantity)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (sku) DO UPDATE SET
+                name = EXCLUDED.name,
+                description = EXCLUDED.description,
+                price = EXCLUDED.price,
+                currency = EXCLUDED.currency,
+                available = EXCLUDED.available,
+                stock_quantity = COALESCE(EXCLUDED.stock_quantity, products.stock_quantity)
+            RETURNING (xmax = 0)
+            "#
+        )
+            .bind(row.sku.trim())
+            .bind(row.name.trim())
+            .bind(&row.description)
+            .bind(row.price)
+            .bind(row.currency.as_str())
+            .bind(row.available)
+            .bind(row.stock_quantity)
+            .fetch_one(&mut *tx)
+            .await;
+
+        match inserted {
+            Ok(true) => report.status = RowStatus::Created,
+            Ok(false) => report.status = RowStatus::Updated,
+            // e.g. lowering stock below what open checkouts have reserved
+            Err(sqlx::Error::Database(e)) => {
+                report.status = RowStatus::Invalid;
+                report.errors.push(e.message().to_string());
+                return Ok(ImportReport { dry_run, applied: false, created: 0, updated: 0, invalid: 1, rows: reports });
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    let created = reports.iter().filter(|r| r.status == RowStatus::Created).count();
+    let updated = reports.iter().filter(|r| r.status == RowStatus::Updated).count();
+    Ok(ImportReport { dry_run, applied: !dry_run, created, updated, invalid: 0, rows: reports })
+}
@@ -0,0 +1,5 @@
+pub mod cli;
+pub mod export;
+pub mod handlers;
+pub mod import;
+pub mod models;
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use crate::money::Currency;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogueRow {
+    pub sku: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub price: i64,
+    pub currency: Currency,
+    #[serde(default = "default_available")]
+    pub available: bool,
+    // Empty leaves stock as it is; new products then do not track stock
+    #[serde(default)]
+    pub stock_quantity: Option<i32>,
+}
+
+fn default_available() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RowStatus {
+    Created,
+    Updated,
+    Invalid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowReport {
+    // 1-based data row, header excluded
+    pub row: usize,
+    pub sku: Option<String>,
+    pub status: RowStatus,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub applied: bool,
+    pub created: usize,
+    pub updated: usize,
+    pub invalid: usize,
+    pub rows: Vec<RowReport>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    pub format: Option<Format>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    pub format: Option<Format>,
+}
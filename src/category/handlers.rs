@@ -0,0 +1,101 @@
+use actix_web::{web, HttpResponse, Responder};
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::AdminUser;
+use crate::locale::models::RequestLocale;
+use crate::locale::translate::translate_categories;
+use crate::product::history::begin_as;
+use crate::category::models::{Category, CategoryUpdate, ProductCategory};
+use crate::routes::models::ApiResponse;
+
+pub async fn get_categories(data: web::Data<AppState>, locale: RequestLocale) -> impl Responder {
+    let categories = sqlx::query_as::<_, Category>("SELECT * FROM categories ORDER BY name")
+        .fetch_all(&data.db_pool)
+        .await;
+
+    let categories = match categories {
+        Ok(mut categories) => translate_categories(&data.db_pool, &mut categories, &locale.0)
+            .await
+            .map(|_| categories),
+        Err(e) => Err(e),
+    };
+
+    match categories {
+        Ok(categories) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "All categories".to_string(),
+            data: categories,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn save_category(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    slug: web::Path<String>,
+    payload: web::Json<CategoryUpdate>,
+) -> impl Responder {
+    let category = sqlx::query_as::<_, Category>(
+        "INSERT INTO categories (slug, name) VALUES ($1, $2) \
+         ON CONFLICT (slug) DO UPDATE SET name = EXCLUDED.name RETURNING *"
+    )
+        .bind(slug.as_str())
+        .bind(payload.name.trim())
+        .fetch_one(&data.db_pool)
+        .await;
+
+    match category {
+        Ok(category) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Category saved".to_string(),
+            data: category,
+        }),
+        Err(sqlx::Error::Database(e)) if e.is_check_violation() => HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Slugs may only contain a-z, 0-9 and -".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn set_product_category(
+    data: web::Data<AppState>,
+    admin: AdminUser,
+    id: web::Path<Uuid>,
+    payload: web::Json<ProductCategory>,
+) -> impl Responder {
+    let mut tx = match begin_as(&data.db_pool, Uuid::parse_str(&admin.claims.sub).ok()).await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let result = sqlx::query("UPDATE products SET category_id = $1 WHERE id = $2")
+        .bind(payload.category_id)
+        .bind(id.into_inner())
+        .execute(&mut *tx)
+        .await;
+
+    match result {
+        Ok(result) if result.rows_affected() > 0 => match tx.commit().await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse {
+                status: "Success".to_string(),
+                msg: "Product category updated".to_string(),
+                data: "{}".to_string(),
+            }),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        },
+        Ok(_) => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Category not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
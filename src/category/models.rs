@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Category {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CategoryUpdate {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductCategory {
+    pub category_id: Option<Uuid>,
+}
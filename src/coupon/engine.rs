@@ -0,0 +1,250 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+use crate::cart::models::PricedLine;
+use crate::coupon::models::Coupon;
+use crate::money::Money;
+use crate::pricing::models::{BaseRate, PricingError};
+
+#[derive(Debug, Clone, Copy)]
+pub enum CouponEffect {
+    Reduce(Money),
+    FreeShipping,
+}
+
+#[derive(Debug)]
+pub enum CouponRejection {
+    Inactive,
+    NotStarted,
+    Expired,
+    UsedUp,
+    UsedUpByUser,
+    MinimumNotMet { minimum: Money },
+    NoEligibleItems,
+}
+
+impl std::fmt::Display for CouponRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CouponRejection::Inactive => write!(f, "This code is no longer active"),
+            CouponRejection::NotStarted => write!(f, "This code is not valid yet"),
+            CouponRejection::Expired => write!(f, "This code has expired"),
+            CouponRejection::UsedUp => write!(f, "This code has been used up"),
+            CouponRejection::UsedUpByUser => write!(f, "You have already used this code"),
+            CouponRejection::MinimumNotMet { minimum } => write!(f, "This code needs an order of at least {}", minimum),
+            CouponRejection::NoEligibleItems => write!(f, "This code does not apply to anything in your cart"),
+        }
+    }
+}
+
+pub fn coupon_label(coupon: &Coupon, amount: Money) -> String {
+    match coupon.percent_off {
+        Some(percent) => format!("Code {}: {}% off", coupon.code, percent),
+        None if coupon.amount_off.is_some() => format!("Code {}: {} off", coupon.code, amount),
+        None => format!("Code {}: free shipping", coupon.code),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CouponUses {
+    pub total: i64,
+    pub by_user: i64,
+}
+
+// Uses held by pending orders count towards the limits
+pub async fn coupon_uses(pool: &PgPool, coupon_id: Uuid, user_id: Uuid) -> Result<CouponUses, sqlx::Error> {
+    let (total, by_user) = sqlx::query_as::<_, (i64, i64)>(
+        "SELECT COUNT(*), COUNT(*) FILTER (WHERE user_id = $2) FROM coupon_redemptions WHERE coupon_id = $1"
+    )
+        .bind(coupon_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(CouponUses { total, by_user })
+}
+
+pub fn evaluate(
+    coupon: &Coupon,
+    uses: CouponUses,
+    rate: &BaseRate,
+    lines: &[PricedLine],
+    subtotal: Money,
+    now: DateTime<Utc>,
+) -> Result<Result<CouponEffect, CouponRejection>, PricingError> {
+    if !coupon.active {
+        return Ok(Err(CouponRejection::Inactive));
+    }
+    if coupon.starts_at.is_some_and(|starts_at| starts_at > now) {
+        return Ok(Err(CouponRejection::NotStarted));
+    }
+    if coupon.ends_at.is_some_and(|ends_at| ends_at <= now) {
+        return Ok(Err(CouponRejection::Expired));
+    }
+
+    if coupon.max_uses.is_some_and(|max| uses.total >= max as i64) {
+        return Ok(Err(CouponRejection::UsedUp));
+    }
+    if coupon.max_uses_per_user.is_some_and(|max| uses.by_user >= max as i64) {
+        return Ok(Err(CouponRejection::UsedUpByUser));
+    }
+
+    let currency = subtotal.currency;
+    if let Some(minimum) = coupon.min_order_amount {
+        let minimum = rate.convert(minimum)?;
+        if subtotal.minor_units < minimum.minor_units {
+            return Ok(Err(CouponRejection::MinimumNotMet { minimum }));
+        }
+    }
+
+    let restricted = !coupon.product_ids.is_empty() || !coupon.category_ids.is_empty();
+    let eligible = lines
+        .iter()
+        .filter(|line| {
+            let product = &line.product.product;
+            !restricted
+                || coupon.product_ids.contains(&product.id)
+                || product.category_id.is_some_and(|id| coupon.category_ids.contains(&id))
+        })
+        .try_fold(Money::zero(currency), |sum, line| sum.checked_add(line.line_total))?;
+    if eligible.minor_units == 0 {
+        return Ok(Err(CouponRejection::NoEligibleItems));
+    }
+
+    let effect = match (coupon.percent_off, coupon.amount_off) {
+        (Some(percent), _) => CouponEffect::Reduce(eligible.share(percent as i64, 100)?),
+        (None, Some(amount)) => {
+            let amount = rate.convert(amount)?;
+            CouponEffect::Reduce(Money::new(amount.minor_units.min(eligible.minor_units), currency))
+        }
+        (None, None) => CouponEffect::FreeShipping,
+    };
+    Ok(Ok(effect))
+}
+
+// Returns false when a concurrent checkout took the last use first
+pub async fn redeem(
+    tx: &mut Transaction<'_, Postgres>,
+    coupon_id: Uuid,
+    user_id: Uuid,
+    order_id: Uuid,
+    discount: Money,
+) -> Result<bool, sqlx::Error> {
+    // Serialises checkouts using the same code while the limits are counted
+    sqlx::query("SELECT id FROM coupons WHERE id = $1 FOR UPDATE")
+        .bind(coupon_id)
+        .execute(&mut **tx)
+        .await?;
+
+    let redeemed = sqlx::query(
+        "INSERT INTO coupon_redemptions (coupon_id, user_id, order_id, amount, currency) \
+         SELECT c.id, $2, $3, $4, $5 FROM coupons c \
+         WHERE c.id = $1 AND c.active \
+           AND (c.max_uses IS NULL OR (SELECT COUNT(*) FROM coupon_redemptions r WHERE r.coupon_id = c.id) < c.max_uses) \
+           AND (c.max_uses_per_user IS NULL OR (SELECT COUNT(*) FROM coupon_redemptions r \
+                WHERE r.coupon_id = c.id AND r.user_id = $2) < c.max_uses_per_user)"
+    )
+        .bind(coupon_id)
+        .bind(user_id)
+        .bind(order_id)
+        .bind(discount.minor_units)
+        .bind(discount.currency.as_str())
+        .execute(&mut **tx)
+        .await?;
+    Ok(redeemed.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use crate::cart::fixtures::{coupon, line, line_of, product, subtotal};
+    use crate::money::Currency;
+    use super::*;
+
+    fn run(coupon: &Coupon, uses: CouponUses, lines: &[PricedLine]) -> Result<CouponEffect, CouponRejection> {
+        evaluate(coupon, uses, &BaseRate::identity(Currency::Inr), lines, subtotal(lines), Utc::now()).unwrap()
+    }
+
+    fn reduction(effect: Result<CouponEffect, CouponRejection>) -> i64 {
+        match effect {
+            Ok(CouponEffect::Reduce(amount)) => amount.minor_units,
+            other => panic!("expected a reduction, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn percent_coupon_rounds_to_the_nearest_minor_unit() {
+        let lines = [line(999, 1), line(250, 2)];
+        assert_eq!(reduction(run(&coupon(Some(15), None), CouponUses::default(), &lines)), 225);
+    }
+
+    #[test]
+    fn fixed_coupon_never_exceeds_the_eligible_spend() {
+        let lines = [line(300, 1)];
+        assert_eq!(reduction(run(&coupon(None, Some(500)), CouponUses::default(), &lines)), 300);
+    }
+
+    #[test]
+    fn fixed_amount_and_minimum_are_converted_from_the_base_currency() {
+        let mut save = coupon(None, Some(1_000));
+        save.min_order_amount = Some(5_000);
+        let rate = BaseRate { currency: Currency::Usd, scaled: Some(120_000_000) };
+        let usd = |price| [line_of(product(Money::new(price, Currency::Usd), None), 1)];
+
+        let lines = usd(60);
+        let effect = evaluate(&save, CouponUses::default(), &rate, &lines, subtotal(&lines), Utc::now());
+        assert_eq!(reduction(effect.unwrap()), 12);
+
+        let lines = usd(59);
+        let effect = evaluate(&save, CouponUses::default(), &rate, &lines, subtotal(&lines), Utc::now());
+        assert!(matches!(
+            effect.unwrap(),
+            Err(CouponRejection::MinimumNotMet { minimum }) if minimum == Money::new(60, Currency::Usd)
+        ));
+    }
+
+    #[test]
+    fn restricted_coupon_only_discounts_eligible_lines() {
+        let category = Uuid::new_v4();
+        let mut save = coupon(Some(50), None);
+        save.category_ids = vec![category];
+        let lines = [line_of(product(Money::new(400, Currency::Inr), Some(category)), 1), line(1_000, 1)];
+        assert_eq!(reduction(run(&save, CouponUses::default(), &lines)), 200);
+
+        let lines = [line(1_000, 1)];
+        assert!(matches!(run(&save, CouponUses::default(), &lines), Err(CouponRejection::NoEligibleItems)));
+    }
+
+    #[test]
+    fn coupon_without_an_amount_waives_shipping() {
+        let lines = [line(100, 1)];
+        assert!(matches!(run(&coupon(None, None), CouponUses::default(), &lines), Ok(CouponEffect::FreeShipping)));
+    }
+
+    #[test]
+    fn coupon_outside_its_window_is_rejected() {
+        let lines = [line(100, 1)];
+        let mut save = coupon(Some(10), None);
+        save.starts_at = Some(Utc::now() + Duration::hours(1));
+        assert!(matches!(run(&save, CouponUses::default(), &lines), Err(CouponRejection::NotStarted)));
+
+        save.starts_at = None;
+        save.ends_at = Some(Utc::now() - Duration::hours(1));
+        assert!(matches!(run(&save, CouponUses::default(), &lines), Err(CouponRejection::Expired)));
+
+        save.ends_at = None;
+        save.active = false;
+        assert!(matches!(run(&save, CouponUses::default(), &lines), Err(CouponRejection::Inactive)));
+    }
+
+    #[test]
+    fn usage_limits_count_overall_and_per_user() {
+        let lines = [line(100, 1)];
+        let mut save = coupon(Some(10), None);
+        save.max_uses = Some(5);
+        save.max_uses_per_user = Some(1);
+
+        assert!(run(&save, CouponUses { total: 4, by_user: 0 }, &lines).is_ok());
+        assert!(matches!(run(&save, CouponUses { total: 5, by_user: 0 }, &lines), Err(CouponRejection::UsedUp)));
+        assert!(matches!(run(&save, CouponUses { total: 1, by_user: 1 }, &lines), Err(CouponRejection::UsedUpByUser)));
+    }
+}
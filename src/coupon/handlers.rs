@@ -0,0 +1,218 @@
+use actix_web::{web, HttpResponse, Responder};
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::{AdminUser, AuthenticatedUser};
+use crate::cart::items::cart_for_user;
+use crate::cart::pricing::{price_cart, price_cart_with};
+use crate::coupon::models::{Coupon, CouponCode, CouponUpdate, CouponUsage};
+use crate::locale::models::RequestLocale;
+use crate::pricing::models::{PricingError, RequestCurrency};
+use crate::routes::models::ApiResponse;
+
+fn pricing_error(e: PricingError) -> HttpResponse {
+    match e {
+        PricingError::Unpriceable { .. } | PricingError::NoRate { .. } | PricingError::Overflow => HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: e.to_string(),
+            data: "No Data".to_string(),
+        }),
+        e => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+// Codes are case-insensitive and stored upper case
+pub async fn save_coupon(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    code: web::Path<String>,
+    payload: web::Json<CouponUpdate>,
+) -> impl Responder {
+    let coupon = sqlx::query_as::<_, Coupon>(
+        "INSERT INTO coupons (code, kind, percent_off, amount_off, starts_at, ends_at, max_uses, \
+            max_uses_per_user, min_order_amount, product_ids, category_ids) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) \
+         ON CONFLICT (code) DO UPDATE SET kind = EXCLUDED.kind, percent_off = EXCLUDED.percent_off, \
+            amount_off = EXCLUDED.amount_off, starts_at = EXCLUDED.starts_at, ends_at = EXCLUDED.ends_at, \
+            max_uses = EXCLUDED.max_uses, max_uses_per_user = EXCLUDED.max_uses_per_user, \
+            min_order_amount = EXCLUDED.min_order_amount, product_ids = EXCLUDED.product_ids, \
+            category_ids = EXCLUDED.category_ids, active = TRUE \
+         RETURNING *"
+    )
+        .bind(code.trim().to_uppercase())
+        .bind(payload.kind.as_str())
+        .bind(payload.percent_off)
+        .bind(payload.amount_off)
+        .bind(payload.starts_at)
+        .bind(payload.ends_at)
+        .bind(payload.max_uses)
+        .bind(payload.max_uses_per_user)
+        .bind(payload.min_order_amount)
+        .bind(&payload.product_ids)
+        .bind(&payload.category_ids)
+        .fetch_one(&data.db_pool)
+        .await;
+
+    match coupon {
+        Ok(coupon) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Coupon saved".to_string(),
+            data: coupon,
+        }),
+        Err(sqlx::Error::Database(e)) if e.is_check_violation() => HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Codes are 3-32 of A-Z, 0-9, _ and -; percent coupons need percent_off (1-100), \
+                  fixed_amount coupons need amount_off, and ends_at must follow starts_at".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn get_coupons(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+) -> impl Responder {
+    let coupons = sqlx::query_as::<_, CouponUsage>(
+        "SELECT c.*, (SELECT COUNT(*) FROM coupon_redemptions r WHERE r.coupon_id = c.id) AS times_used \
+         FROM coupons c ORDER BY c.created_at DESC"
+    )
+        .fetch_all(&data.db_pool)
+        .await;
+
+    match coupons {
+        Ok(coupons) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Coupons".to_string(),
+            data: coupons,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+// Redemptions are kept for the order history
+pub async fn deactivate_coupon(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    code: web::Path<String>,
+) -> impl Responder {
+    let result = sqlx::query("UPDATE coupons SET active = FALSE WHERE code = $1")
+        .bind(code.trim().to_uppercase())
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(result) if result.rows_affected() > 0 => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Coupon deactivated".to_string(),
+            data: "{}".to_string(),
+        }),
+        Ok(_) => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Coupon not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn apply_coupon(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    currency: RequestCurrency,
+    locale: RequestLocale,
+    payload: web::Json<CouponCode>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let coupon = sqlx::query_as::<_, Coupon>("SELECT * FROM coupons WHERE code = $1")
+        .bind(payload.code.trim().to_uppercase())
+        .fetch_optional(&data.db_pool)
+        .await;
+    let coupon = match coupon {
+        Ok(Some(coupon)) => coupon,
+        Ok(None) => return HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Unknown coupon code".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let mut tx = match data.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+    let cart_id = match cart_for_user(&mut tx, user_id).await {
+        Ok(cart_id) => cart_id,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+    if let Err(e) = tx.commit().await {
+        return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+    }
+
+    let priced = match price_cart_with(&data, cart_id, user_id, Some(&coupon), currency.0, &locale.0).await {
+        Ok(priced) => priced,
+        Err(e) => return pricing_error(e),
+    };
+    if let Some(status) = priced.coupon.as_ref().filter(|status| !status.applied) {
+        return HttpResponse::Conflict().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: status.reason.clone().unwrap_or_default(),
+            data: "No Data".to_string(),
+        });
+    }
+
+    let saved = sqlx::query("UPDATE shopping_cart SET coupon_id = $1 WHERE id = $2")
+        .bind(coupon.id)
+        .bind(cart_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match saved {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Coupon applied".to_string(),
+            data: priced,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn remove_coupon(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    currency: RequestCurrency,
+    locale: RequestLocale,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let cart_id = sqlx::query_scalar::<_, Uuid>(
+        "UPDATE shopping_cart SET coupon_id = NULL WHERE user_id = $1 RETURNING id"
+    )
+        .bind(user_id)
+        .fetch_optional(&data.db_pool)
+        .await;
+
+    match cart_id {
+        Ok(Some(cart_id)) => match price_cart(&data, cart_id, currency.0, &locale.0).await {
+            Ok(priced) => HttpResponse::Ok().json(ApiResponse {
+                status: "Success".to_string(),
+                msg: "Coupon removed".to_string(),
+                data: priced,
+            }),
+            Err(e) => pricing_error(e),
+        },
+        Ok(None) => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "No cart found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
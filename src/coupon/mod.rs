@@ -0,0 +1,3 @@
+pub mod engine;
+pub mod handlers;
+pub mod models;
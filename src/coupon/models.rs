@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CouponKind {
+    Percent,
+    FixedAmount,
+    FreeShipping,
+}
+
+impl CouponKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CouponKind::Percent => "percent",
+            CouponKind::FixedAmount => "fixed_amount",
+            CouponKind::FreeShipping => "free_shipping",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Coupon {
+    pub id: Uuid,
+    pub code: String,
+    pub kind: String,
+    pub percent_off: Option<i32>,
+    // Base currency minor units
+    pub amount_off: Option<i64>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub ends_at: Option<DateTime<Utc>>,
+    pub max_uses: Option<i32>,
+    pub max_uses_per_user: Option<i32>,
+    // Base currency minor units
+    pub min_order_amount: Option<i64>,
+    pub product_ids: Vec<Uuid>,
+    pub category_ids: Vec<Uuid>,
+    pub active: bool,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct CouponUsage {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub coupon: Coupon,
+    pub times_used: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CouponUpdate {
+    pub kind: CouponKind,
+    pub percent_off: Option<i32>,
+    pub amount_off: Option<i64>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub max_uses: Option<i32>,
+    pub max_uses_per_user: Option<i32>,
+    pub min_order_amount: Option<i64>,
+    #[serde(default)]
+    pub product_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub category_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CouponCode {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CouponStatus {
+    pub code: String,
+    pub applied: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::env;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+use crate::digital::models::DownloadLink;
+use crate::digital::signing::signed_url;
+
+fn download_limit() -> i32 {
+    env::var("DOWNLOAD_LIMIT")
+        .ok()
+        .and_then(|v| v.parse::<i32>().ok())
+        .filter(|limit| *limit > 0)
+        .unwrap_or(5)
+}
+
+// Safe to call more than once for the same order
+pub async fn grant_downloads(tx: &mut Transaction<'_, Postgres>, order_id: Uuid) -> Result<u64, sqlx::Error> {
+    let granted = sqlx::query(
+        "INSERT INTO download_grants (order_id, user_id, product_id, max_downloads) \
+         SELECT DISTINCT o.id, o.user_id, oi.product_id, $2 \
+         FROM orders o \
+         JOIN order_items oi ON oi.order_id = o.id \
+         JOIN products p ON p.id = oi.product_id \
+         WHERE o.id = $1 AND p.is_digital \
+         ON CONFLICT (order_id, product_id) DO NOTHING"
+    )
+        .bind(order_id)
+        .bind(download_limit())
+        .execute(&mut **tx)
+        .await?;
+    Ok(granted.rows_affected())
+}
+
+#[derive(sqlx::FromRow)]
+struct GrantedFile {
+    grant_id: Uuid,
+    order_id: Uuid,
+    product_id: Uuid,
+    file_id: Uuid,
+    file_name: String,
+    size_bytes: i64,
+    downloads_remaining: i32,
+}
+
+pub async fn download_links(pool: &PgPool, user_id: Uuid) -> Result<HashMap<Uuid, Vec<DownloadLink>>, sqlx::Error> {
+    let files = sqlx::query_as::<_, GrantedFile>(
+        "SELECT g.id AS grant_id, g.order_id, g.product_id, f.id AS file_id, f.file_name, f.size_bytes, \
+                g.max_downloads - g.downloads_used AS downloads_remaining \
+         FROM download_grants g \
+         JOIN orders o ON o.id = g.order_id \
+         JOIN product_files f ON f.product_id = g.product_id \
+         WHERE g.user_id = $1 AND o.status = 'paid' \
+         ORDER BY f.created_at"
+    )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    let mut links: HashMap<Uuid, Vec<DownloadLink>> = HashMap::new();
+    for file in files {
+        let (url, expires_at) = signed_url(file.grant_id, file.file_id);
+        links.entry(file.order_id).or_default().push(DownloadLink {
+            product_id: file.product_id,
+            file_id: file.file_id,
+            file_name: file.file_name,
+            size_bytes: file.size_bytes,
+            downloads_remaining: file.downloads_remaining,
+            url,
+            expires_at,
+        });
+    }
+    Ok(links)
+}
@@ -0,0 +1,292 @@
+use actix_multipart::Multipart;
+use actix_web::http::header::ContentDisposition;
+use actix_web::{web, HttpResponse, Responder};
+use futures::StreamExt;
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::AdminUser;
+use crate::digital::models::{file_key, DownloadSignature, ProductFile};
+use crate::digital::signing::verify;
+use crate::product::history::begin_as;
+use crate::routes::models::ApiResponse;
+use crate::storage::StorageError;
+
+fn max_file_bytes() -> usize {
+    std::env::var("MAX_DIGITAL_FILE_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(100 * 1024 * 1024)
+}
+
+fn clean_file_name(name: Option<&str>) -> String {
+    let name = name
+        .and_then(|name| name.rsplit(['/', '\\']).next())
+        .map(str::trim)
+        .unwrap_or_default();
+    if name.is_empty() { "download".to_string() } else { name.to_string() }
+}
+
+// A failed upload leaves none of its files behind
+async fn discard_uploads(data: &AppState, product_id: Uuid, file_ids: &[Uuid]) {
+    if let Err(e) = sqlx::query("DELETE FROM product_files WHERE id = ANY($1)")
+        .bind(file_ids)
+        .execute(&data.db_pool)
+        .await
+    {
+        log::warn!("Failed to delete files of a failed upload: {}", e);
+        return;
+    }
+    for file_id in file_ids {
+        let key = file_key(product_id, *file_id);
+        if let Err(e) = data.blob_store.delete(&key).await {
+            log::warn!("Failed to delete blob {}: {}", key, e);
+        }
+    }
+}
+
+async fn sync_is_digital(data: &AppState, admin: &AdminUser, product_id: Uuid) -> Result<(), sqlx::Error> {
+    let mut tx = begin_as(&data.db_pool, Uuid::parse_str(&admin.claims.sub).ok()).await?;
+    sqlx::query(
+        "UPDATE products SET is_digital = EXISTS (SELECT 1 FROM product_files WHERE product_id = $1) \
+         WHERE id = $1 AND is_digital <> EXISTS (SELECT 1 FROM product_files WHERE product_id = $1)"
+    )
+        .bind(product_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+pub async fn upload_product_files(
+    data: web::Data<AppState>,
+    admin: AdminUser,
+    id: web::Path<Uuid>,
+    mut payload: Multipart,
+) -> impl Responder {
+    let product_id = id.into_inner();
+
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM products WHERE id = $1)")
+        .bind(product_id)
+        .fetch_one(&data.db_pool)
+        .await;
+
+    match exists {
+        Ok(true) => {}
+        Ok(false) => return HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+
+    let limit = max_file_bytes();
+    let mut uploaded = vec![];
+    let mut stored = vec![];
+
+    while let Some(field) = payload.next().await {
+        let mut field = match field {
+            Ok(field) => field,
+            Err(e) => {
+                discard_uploads(&data, product_id, &stored).await;
+                return HttpResponse::BadRequest().body(format!("Invalid multipart body: {}", e));
+            }
+        };
+
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let file_name = clean_file_name(field.content_disposition().and_then(|cd| cd.get_filename()));
+        let content_type = field
+            .content_type()
+            .map(|mime| mime.to_string())
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.next().await {
+            match chunk {
+                Ok(chunk) if bytes.len() + chunk.len() <= limit => bytes.extend_from_slice(&chunk),
+                Ok(_) => {
+                    discard_uploads(&data, product_id, &stored).await;
+                    return HttpResponse::PayloadTooLarge().body(format!("Files are limited to {} bytes", limit));
+                }
+                Err(e) => {
+                    discard_uploads(&data, product_id, &stored).await;
+                    return HttpResponse::BadRequest().body(format!("Invalid multipart body: {}", e));
+                }
+            }
+        }
+
+        let file_id = Uuid::new_v4();
+        let size_bytes = bytes.len() as i64;
+        if let Err(e) = data.blob_store.put(&file_key(product_id, file_id), bytes, &content_type).await {
+            discard_uploads(&data, product_id, &stored).await;
+            return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+        }
+        stored.push(file_id);
+
+        let file = sqlx::query_as::<_, ProductFile>(
+            "INSERT INTO product_files (id, product_id, file_name, content_type, size_bytes) \
+             VALUES ($1, $2, $3, $4, $5) RETURNING *"
+        )
+            .bind(file_id)
+            .bind(product_id)
+            .bind(&file_name)
+            .bind(&content_type)
+            .bind(size_bytes)
+            .fetch_one(&data.db_pool)
+            .await;
+
+        match file {
+            Ok(file) => uploaded.push(file),
+            Err(e) => {
+                discard_uploads(&data, product_id, &stored).await;
+                return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+            }
+        }
+    }
+
+    if uploaded.is_empty() {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "No `file` fields in the upload".to_string(),
+            data: "No Data".to_string(),
+        });
+    }
+
+    if let Err(e) = sync_is_digital(&data, &admin, product_id).await {
+        return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+    }
+
+    HttpResponse::Ok().json(ApiResponse {
+        status: "Success".to_string(),
+        msg: "Files uploaded".to_string(),
+        data: uploaded,
+    })
+}
+
+pub async fn get_product_files(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    id: web::Path<Uuid>,
+) -> impl Responder {
+    let files = sqlx::query_as::<_, ProductFile>(
+        "SELECT * FROM product_files WHERE product_id = $1 ORDER BY created_at"
+    )
+        .bind(id.into_inner())
+        .fetch_all(&data.db_pool)
+        .await;
+
+    match files {
+        Ok(files) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Product files".to_string(),
+            data: files,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn delete_product_file(
+    data: web::Data<AppState>,
+    admin: AdminUser,
+    path: web::Path<(Uuid, Uuid)>,
+) -> impl Responder {
+    let (product_id, file_id) = path.into_inner();
+
+    let deleted = sqlx::query_scalar::<_, Uuid>(
+        "DELETE FROM product_files WHERE id = $1 AND product_id = $2 RETURNING id"
+    )
+        .bind(file_id)
+        .bind(product_id)
+        .fetch_optional(&data.db_pool)
+        .await;
+
+    match deleted {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "File not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+
+    let key = file_key(product_id, file_id);
+    if let Err(e) = data.blob_store.delete(&key).await {
+        log::warn!("Failed to delete blob {}: {}", key, e);
+    }
+
+    // The last file going away makes it a regular product again
+    match sync_is_digital(&data, &admin, product_id).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "File deleted".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+// The signature is the authorization, so the link works without a token until it expires
+pub async fn download(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+    query: web::Query<DownloadSignature>,
+) -> impl Responder {
+    let (grant_id, file_id) = path.into_inner();
+
+    if !verify(grant_id, file_id, query.expires, &query.signature) {
+        return HttpResponse::Forbidden().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "This download link is invalid or has expired".to_string(),
+            data: "No Data".to_string(),
+        });
+    }
+
+    // Counting and checking the limit in one statement keeps parallel
+    // requests from going over it.
+    let claimed = sqlx::query_as::<_, (Uuid, String, String)>(
+        "UPDATE download_grants g SET downloads_used = g.downloads_used + 1 \
+         FROM product_files f, orders o \
+         WHERE g.id = $1 AND f.id = $2 AND f.product_id = g.product_id \
+           AND o.id = g.order_id AND o.status = 'paid' AND g.downloads_used < g.max_downloads \
+         RETURNING f.product_id, f.file_name, f.content_type"
+    )
+        .bind(grant_id)
+        .bind(file_id)
+        .fetch_optional(&data.db_pool)
+        .await;
+
+    let (product_id, file_name, content_type) = match claimed {
+        Ok(Some(file)) => file,
+        Ok(None) => return HttpResponse::Gone().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "This download is no longer available".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    match data.blob_store.get(&file_key(product_id, file_id)).await {
+        Ok(bytes) => HttpResponse::Ok()
+            .content_type(content_type)
+            .insert_header(ContentDisposition::attachment(file_name))
+            .insert_header(("Cache-Control", "private, no-store"))
+            .body(bytes),
+        Err(e) => {
+            // A failed delivery should not use up one of the buyer's downloads
+            if let Err(e) = sqlx::query("UPDATE download_grants SET downloads_used = downloads_used - 1 WHERE id = $1")
+                .bind(grant_id)
+                .execute(&data.db_pool)
+                .await
+            {
+                log::warn!("Failed to refund download for grant {}: {}", grant_id, e);
+            }
+            match e {
+                StorageError::NotFound => HttpResponse::NotFound().finish(),
+                e => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+            }
+        }
+    }
+}
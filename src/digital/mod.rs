@@ -0,0 +1,4 @@
+pub mod grants;
+pub mod handlers;
+pub mod models;
+pub mod signing;
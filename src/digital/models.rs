@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// The blob itself is at file_key(product_id, id)
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ProductFile {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+pub fn file_key(product_id: Uuid, file_id: Uuid) -> String {
+    format!("digital/{}/{}", product_id, file_id)
+}
+
+#[derive(Debug, Serialize)]
+pub struct DownloadLink {
+    pub product_id: Uuid,
+    pub file_id: Uuid,
+    pub file_name: String,
+    pub size_bytes: i64,
+    // Shared by all files of the product
+    pub downloads_remaining: i32,
+    pub url: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DownloadSignature {
+    pub expires: i64,
+    pub signature: String,
+}
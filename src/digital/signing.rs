@@ -0,0 +1,106 @@
+use std::env;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+use crate::mailer::app_base_url;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn link_ttl() -> Duration {
+    let secs = env::var("DOWNLOAD_URL_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(900);
+    Duration::seconds(secs)
+}
+
+fn secret() -> String {
+    env::var("DOWNLOAD_SIGNING_SECRET")
+        .or_else(|_| env::var("JWT_SECRET"))
+        .expect("DOWNLOAD_SIGNING_SECRET or JWT_SECRET must be set")
+}
+
+fn mac_for(secret: &str, grant_id: Uuid, file_id: Uuid, expires: i64) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(format!("{}:{}:{}", grant_id, file_id, expires).as_bytes());
+    mac
+}
+
+fn sign(secret: &str, grant_id: Uuid, file_id: Uuid, expires: i64) -> String {
+    mac_for(secret, grant_id, file_id, expires)
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn check(secret: &str, grant_id: Uuid, file_id: Uuid, expires: i64, signature: &str, now: i64) -> bool {
+    if expires < now || !signature.len().is_multiple_of(2) || !signature.is_ascii() {
+        return false;
+    }
+    let bytes: Result<Vec<u8>, _> = (0..signature.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&signature[i..i + 2], 16))
+        .collect();
+    match bytes {
+        Ok(bytes) => mac_for(secret, grant_id, file_id, expires).verify_slice(&bytes).is_ok(),
+        Err(_) => false,
+    }
+}
+
+pub fn signed_url(grant_id: Uuid, file_id: Uuid) -> (String, DateTime<Utc>) {
+    let expires_at = Utc::now() + link_ttl();
+    let expires = expires_at.timestamp();
+    let signature = sign(&secret(), grant_id, file_id, expires);
+    let url = format!(
+        "{}/downloads/{}/{}?expires={}&signature={}",
+        app_base_url(), grant_id, file_id, expires, signature
+    );
+    (url, expires_at)
+}
+
+// verify_slice compares in constant time
+pub fn verify(grant_id: Uuid, file_id: Uuid, expires: i64, signature: &str) -> bool {
+    check(&secret(), grant_id, file_id, expires, signature, Utc::now().timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "test secret";
+    const NOW: i64 = 1_800_000_000;
+
+    #[test]
+    fn signature_verifies_until_expiry() {
+        let (grant, file) = (Uuid::new_v4(), Uuid::new_v4());
+        let signature = sign(SECRET, grant, file, NOW + 60);
+        assert_eq!(signature.len(), 64);
+        assert!(check(SECRET, grant, file, NOW + 60, &signature, NOW));
+        assert!(check(SECRET, grant, file, NOW + 60, &signature, NOW + 60));
+        assert!(!check(SECRET, grant, file, NOW + 60, &signature, NOW + 61));
+    }
+
+    #[test]
+    fn signature_is_bound_to_the_link() {
+        let (grant, file) = (Uuid::new_v4(), Uuid::new_v4());
+        let signature = sign(SECRET, grant, file, NOW + 60);
+        assert!(!check(SECRET, grant, Uuid::new_v4(), NOW + 60, &signature, NOW));
+        assert!(!check(SECRET, Uuid::new_v4(), file, NOW + 60, &signature, NOW));
+        assert!(!check(SECRET, grant, file, NOW + 3_600, &signature, NOW));
+        assert!(!check("another secret", grant, file, NOW + 60, &signature, NOW));
+    }
+
+    #[test]
+    fn malformed_signatures_are_rejected() {
+        let (grant, file) = (Uuid::new_v4(), Uuid::new_v4());
+        let signature = sign(SECRET, grant, file, NOW + 60);
+        assert!(!check(SECRET, grant, file, NOW + 60, &signature[..63], NOW));
+        assert!(!check(SECRET, grant, file, NOW + 60, &signature[..62], NOW));
+        assert!(!check(SECRET, grant, file, NOW + 60, &"zz".repeat(32), NOW));
+        assert!(!check(SECRET, grant, file, NOW + 60, "éé", NOW));
+        assert!(!check(SECRET, grant, file, NOW + 60, "", NOW));
+    }
+}
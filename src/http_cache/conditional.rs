@@ -0,0 +1,142 @@
+use std::time::SystemTime;
+use actix_web::http::header::{
+    self, ContentType, EntityTag, Header, HttpDate, IfModifiedSince, IfNoneMatch, LastModified, ETag,
+};
+use actix_web::{HttpRequest, HttpResponse, HttpResponseBuilder};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::http_cache::models::{CachedResponse, Validators};
+use crate::money::Currency;
+
+pub async fn listing_validators(
+    pool: &PgPool,
+    uri: &str,
+    currency: Currency,
+    locale: &str,
+) -> Result<Validators, sqlx::Error> {
+    let (_, validators) = catalogue_validators(pool, None, uri, currency, locale).await?;
+    Ok(validators)
+}
+
+// None if there is no such product
+pub async fn product_validators(
+    pool: &PgPool,
+    product_id: Uuid,
+    uri: &str,
+    currency: Currency,
+    locale: &str,
+) -> Result<Option<Validators>, sqlx::Error> {
+    let (exists, validators) = catalogue_validators(pool, Some(product_id), uri, currency, locale).await?;
+    Ok(exists.then_some(validators))
+}
+
+// The tag covers every input of the response, including time-based price changes
+async fn catalogue_validators(
+    pool: &PgPool,
+    product_id: Option<Uuid>,
+    uri: &str,
+    currency: Currency,
+    locale: &str,
+) -> Result<(bool, Validators), sqlx::Error> {
+    let row = sqlx::query_as::<_, (bool, String, Option<DateTime<Utc>>)>(
+        r#"
+        WITH scope AS (
+            SELECT id, version, updated_at FROM products
+            WHERE $1::UUID IS NULL OR id = $1
+        ),
+        price_events AS (
+            SELECT s.starts_at AS at FROM product_sales s JOIN scope ON scope.id = s.product_id
+            UNION ALL
+            SELECT s.ends_at FROM product_sales s JOIN scope ON scope.id = s.product_id
+            UNION ALL
+            SELECT s.ends_at + INTERVAL '30 days' FROM product_sales s JOIN scope ON scope.id = s.product_id
+            UNION ALL
+            SELECT h.recorded_at + INTERVAL '30 days' FROM product_price_history h JOIN scope ON scope.id = h.product_id
+        ),
+        state AS (
+            SELECT
+                (SELECT string_agg(id::TEXT || ':' || version || ':' || (EXTRACT(EPOCH FROM updated_at) * 1000000)::BIGINT, ',' ORDER BY id) FROM scope) AS products,
+                (SELECT MAX(updated_at) FROM scope) AS products_at,
+                (SELECT MAX(at) FROM price_events WHERE at <= now()) AS prices_at,
+                (SELECT string_agg(currency || ':' || units_per_base, ',' ORDER BY currency) FROM exchange_rates) AS rates,
+                (SELECT MAX(updated_at) FROM exchange_rates) AS rates_at,
+                (SELECT string_agg(to_jsonb(d)::TEXT, ',' ORDER BY d.key) FROM attribute_definitions d) AS definitions
+        )
+        SELECT
+            EXISTS (SELECT 1 FROM scope),
+            md5(concat_ws('|', $2::TEXT, $3::TEXT, $4::TEXT, products, prices_at::TEXT, rates, definitions)),
+            GREATEST(products_at, prices_at, rates_at)
+        FROM state
+        "#
+    )
+        .bind(product_id)
+        .bind(uri)
+        .bind(currency.as_str())
+        .bind(locale)
+        .fetch_one(pool)
+        .await?;
+
+    let (exists, etag, last_modified) = row;
+    Ok((exists, Validators { etag, last_modified }))
+}
+
+impl Validators {
+    fn entity_tag(&self) -> EntityTag {
+        EntityTag::new_strong(self.etag.clone())
+    }
+
+    // If-None-Match takes precedence over If-Modified-Since, as in RFC 9110
+    pub fn is_fresh(&self, req: &HttpRequest) -> bool {
+        if req.headers().contains_key(header::IF_NONE_MATCH) {
+            return match IfNoneMatch::parse(req) {
+                Ok(IfNoneMatch::Any) => true,
+                Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&self.entity_tag())),
+                Err(_) => false,
+            };
+        }
+
+        match (IfModifiedSince::parse(req), self.last_modified) {
+            (Ok(IfModifiedSince(since)), Some(last_modified)) => {
+                // HTTP dates have whole-second precision
+                SystemTime::from(since).duration_since(SystemTime::UNIX_EPOCH)
+                    .map(|since| last_modified.timestamp() <= since.as_secs() as i64)
+                    .unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn response(&self, mut builder: HttpResponseBuilder, cache_control: &str) -> HttpResponseBuilder {
+        builder
+            .insert_header(ETag(self.entity_tag()))
+            .insert_header((header::CACHE_CONTROL, cache_control))
+            // Prices follow the X-Currency header and the signed-in user's preference
+            .insert_header((header::VARY, "Accept-Language, Authorization, X-Currency"));
+        if let Some(last_modified) = self.last_modified {
+            builder.insert_header(LastModified(HttpDate::from(SystemTime::from(last_modified))));
+        }
+        builder
+    }
+
+    pub fn not_modified(&self, cache_control: &str) -> HttpResponse {
+        self.response(HttpResponse::NotModified(), cache_control).finish()
+    }
+}
+
+impl CachedResponse {
+    pub fn new<T: Serialize>(validators: Validators, payload: &T) -> serde_json::Result<Self> {
+        Ok(CachedResponse { validators, body: serde_json::to_string(payload)? })
+    }
+
+    pub fn respond(&self, req: &HttpRequest, cache_control: &str) -> HttpResponse {
+        if self.validators.is_fresh(req) {
+            return self.validators.not_modified(cache_control);
+        }
+        self.validators
+            .response(HttpResponse::Ok(), cache_control)
+            .content_type(ContentType::json())
+            .body(self.body.clone())
+    }
+}
@@ -0,0 +1,64 @@
+use std::env;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy)]
+pub enum CachedRoute {
+    ProductList,
+    ProductDetail,
+}
+
+impl CachedRoute {
+    fn env_var(self) -> &'static str {
+        match self {
+            CachedRoute::ProductList => "CACHE_CONTROL_PRODUCT_LIST",
+            CachedRoute::ProductDetail => "CACHE_CONTROL_PRODUCT_DETAIL",
+        }
+    }
+
+    fn default_policy(self) -> &'static str {
+        match self {
+            CachedRoute::ProductList => "public, max-age=60",
+            CachedRoute::ProductDetail => "public, max-age=300",
+        }
+    }
+}
+
+// Overridable with CACHE_CONTROL_<ROUTE>
+#[derive(Debug, Clone)]
+pub struct CachePolicies {
+    product_list: String,
+    product_detail: String,
+}
+
+impl CachePolicies {
+    pub fn from_env() -> Self {
+        let read = |route: CachedRoute| {
+            env::var(route.env_var()).unwrap_or_else(|_| route.default_policy().to_string())
+        };
+        CachePolicies {
+            product_list: read(CachedRoute::ProductList),
+            product_detail: read(CachedRoute::ProductDetail),
+        }
+    }
+
+    pub fn for_route(&self, route: CachedRoute) -> &str {
+        match route {
+            CachedRoute::ProductList => &self.product_list,
+            CachedRoute::ProductDetail => &self.product_detail,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Validators {
+    // Without the surrounding quotes
+    pub etag: String,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub validators: Validators,
+    pub body: String,
+}
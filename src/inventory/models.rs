@@ -0,0 +1,24 @@
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum ReservationError {
+    InsufficientStock { product_id: Uuid },
+    Db(sqlx::Error),
+}
+
+impl std::fmt::Display for ReservationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReservationError::InsufficientStock { product_id } => {
+                write!(f, "Insufficient stock for product {}", product_id)
+            }
+            ReservationError::Db(e) => write!(f, "DB error: {}", e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for ReservationError {
+    fn from(e: sqlx::Error) -> Self {
+        ReservationError::Db(e)
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+use crate::inventory::models::ReservationError;
+
+// Stripe only accepts sessions that expire between 30 minutes and 24 hours out
+pub fn reservation_ttl() -> chrono::Duration {
+    let minutes = env::var("RESERVATION_TTL_MINUTES")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(30)
+        .clamp(30, 24 * 60);
+    chrono::Duration::minutes(minutes)
+}
+
+pub async fn reserve_for_order(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: Uuid,
+    items: &[(Uuid, i32)],
+    expires_at: DateTime<Utc>,
+) -> Result<(), ReservationError> {
+    // Rows are locked in id order so concurrent checkouts cannot deadlock
+    let mut quantities: BTreeMap<Uuid, i32> = BTreeMap::new();
+    for (product_id, quantity) in items {
+        *quantities.entry(*product_id).or_insert(0) += quantity;
+    }
+
+    for (product_id, quantity) in quantities {
+        let reserved = sqlx::query(
+            "UPDATE products SET reserved_quantity = reserved_quantity + $2 \
+             WHERE id = $1 AND (stock_quantity IS NULL OR stock_quantity - reserved_quantity >= $2)"
+        )
+            .bind(product_id)
+            .bind(quantity)
+            .execute(&mut **tx)
+            .await?;
+
+        if reserved.rows_affected() == 0 {
+            return Err(ReservationError::InsufficientStock { product_id });
+        }
+
+        sqlx::query(
+            "INSERT INTO stock_reservations (order_id, product_id, quantity, expires_at) VALUES ($1, $2, $3, $4)"
+        )
+            .bind(order_id)
+            .bind(product_id)
+            .bind(quantity)
+            .bind(expires_at)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn commit_for_order(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        WITH committed AS (
+            UPDATE stock_reservations SET status = 'committed'
+            WHERE order_id = $1 AND status = 'active'
+            RETURNING product_id, quantity
+        )
+        UPDATE products p
+        SET stock_quantity = p.stock_quantity - c.quantity,
+            reserved_quantity = p.reserved_quantity - c.quantity
+        FROM committed c
+        WHERE p.id = c.product_id
+        "#
+    )
+        .bind(order_id)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+// For orders paid after their reservation lapsed
+pub async fn reclaim_for_order(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: Uuid,
+) -> Result<(), ReservationError> {
+    let released = sqlx::query_as::<_, (Uuid, i32)>(
+        "SELECT product_id, quantity FROM stock_reservations \
+         WHERE order_id = $1 AND status = 'released' ORDER BY product_id"
+    )
+        .bind(order_id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+    for (product_id, quantity) in released {
+        let reserved = sqlx::query(
+            "UPDATE products SET reserved_quantity = reserved_quantity + $2 \
+             WHERE id = $1 AND (stock_quantity IS NULL OR stock_quantity - reserved_quantity >= $2)"
+        )
+            .bind(product_id)
+            .bind(quantity)
+            .execute(&mut **tx)
+            .await?;
+
+        if reserved.rows_affected() == 0 {
+            return Err(ReservationError::InsufficientStock { product_id });
+        }
+    }
+
+    sqlx::query("UPDATE stock_reservations SET status = 'active' WHERE order_id = $1 AND status = 'released'")
+        .bind(order_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+pub async fn release_for_order(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        WITH released AS (
+            UPDATE stock_reservations SET status = 'released'
+            WHERE order_id = $1 AND status = 'active'
+            RETURNING product_id, quantity
+        )
+        UPDATE products p
+        SET reserved_quantity = p.reserved_quantity - r.quantity
+        FROM released r
+        WHERE p.id = r.product_id
+        "#
+    )
+        .bind(order_id)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+// Lets the webhook of a payment made just before expiry arrive before the sweep
+const EXPIRY_GRACE_MINUTES: i32 = 15;
+
+pub async fn release_expired(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let mut expired_orders: Vec<Uuid> = sqlx::query_scalar(
+        "SELECT order_id FROM stock_reservations \
+         WHERE status = 'active' AND expires_at < now() - make_interval(mins => $1) \
+         FOR UPDATE SKIP LOCKED"
+    )
+        .bind(EXPIRY_GRACE_MINUTES)
+        .fetch_all(&mut *tx)
+        .await?;
+    expired_orders.sort();
+    expired_orders.dedup();
+
+    let mut released = 0;
+    for order_id in &expired_orders {
+        released += release_for_order(&mut tx, *order_id).await?;
+    }
+
+    let expired: Vec<Uuid> = sqlx::query_scalar(
+        "UPDATE orders SET status = 'expired' WHERE id = ANY($1) AND status = 'pending' RETURNING id"
+    )
+        .bind(&expired_orders)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    // An expired order gives its coupon use back
+    sqlx::query("DELETE FROM coupon_redemptions WHERE order_id = ANY($1)")
+        .bind(&expired)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(released)
+}
+
+pub fn spawn_expiry_job(pool: PgPool) {
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            match release_expired(&pool).await {
+                Ok(0) => {}
+                Ok(n) => info!("Released stock for {} expired reservations", n),
+                Err(e) => error!("Failed to release expired reservations: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,180 @@
+use actix_web::{web, HttpResponse, Responder};
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::AdminUser;
+use crate::locale::models::{CategoryTranslationUpdate, ProductTranslation, ProductTranslationUpdate};
+use crate::routes::models::ApiResponse;
+
+fn translatable_locale(data: &AppState, locale: &str) -> Option<String> {
+    data.locales
+        .resolve(locale)
+        .filter(|resolved| *resolved == locale.to_lowercase() && *resolved != data.locales.default)
+}
+
+fn untranslatable_locale(data: &AppState) -> HttpResponse {
+    HttpResponse::BadRequest().json(ApiResponse {
+        status: "Error".to_string(),
+        msg: format!(
+            "Translations are for the supported locales other than {}: {}",
+            data.locales.default,
+            data.locales.supported.join(", ")
+        ),
+        data: "No Data".to_string(),
+    })
+}
+
+pub async fn get_product_translations(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    id: web::Path<Uuid>,
+) -> impl Responder {
+    let translations = sqlx::query_as::<_, ProductTranslation>(
+        "SELECT locale, name, description FROM product_translations WHERE product_id = $1 ORDER BY locale"
+    )
+        .bind(id.into_inner())
+        .fetch_all(&data.db_pool)
+        .await;
+
+    match translations {
+        Ok(translations) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Product translations".to_string(),
+            data: translations,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn save_product_translation(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    path: web::Path<(Uuid, String)>,
+    payload: web::Json<ProductTranslationUpdate>,
+) -> impl Responder {
+    let (product_id, locale) = path.into_inner();
+    let Some(locale) = translatable_locale(&data, &locale) else {
+        return untranslatable_locale(&data);
+    };
+
+    let translation = sqlx::query_as::<_, ProductTranslation>(
+        "INSERT INTO product_translations (product_id, locale, name, description) VALUES ($1, $2, $3, $4) \
+         ON CONFLICT (product_id, locale) DO UPDATE SET name = EXCLUDED.name, description = EXCLUDED.description \
+         RETURNING locale, name, description"
+    )
+        .bind(product_id)
+        .bind(&locale)
+        .bind(payload.name.trim())
+        .bind(&payload.description)
+        .fetch_one(&data.db_pool)
+        .await;
+
+    match translation {
+        Ok(translation) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Translation saved".to_string(),
+            data: translation,
+        }),
+        Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn delete_product_translation(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    path: web::Path<(Uuid, String)>,
+) -> impl Responder {
+    let (product_id, locale) = path.into_inner();
+
+    let result = sqlx::query("DELETE FROM product_translations WHERE product_id = $1 AND locale = $2")
+        .bind(product_id)
+        .bind(locale.to_lowercase())
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(done) if done.rows_affected() == 0 => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Translation not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Translation removed".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn save_category_translation(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    path: web::Path<(String, String)>,
+    payload: web::Json<CategoryTranslationUpdate>,
+) -> impl Responder {
+    let (slug, locale) = path.into_inner();
+    let Some(locale) = translatable_locale(&data, &locale) else {
+        return untranslatable_locale(&data);
+    };
+
+    let result = sqlx::query(
+        "INSERT INTO category_translations (category_id, locale, name) \
+         SELECT id, $2, $3 FROM categories WHERE slug = $1 \
+         ON CONFLICT (category_id, locale) DO UPDATE SET name = EXCLUDED.name"
+    )
+        .bind(&slug)
+        .bind(&locale)
+        .bind(payload.name.trim())
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(done) if done.rows_affected() == 0 => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Category not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Translation saved".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn delete_category_translation(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (slug, locale) = path.into_inner();
+
+    let result = sqlx::query(
+        "DELETE FROM category_translations ct USING categories c \
+         WHERE ct.category_id = c.id AND c.slug = $1 AND ct.locale = $2"
+    )
+        .bind(&slug)
+        .bind(locale.to_lowercase())
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(done) if done.rows_affected() == 0 => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Translation not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Translation removed".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
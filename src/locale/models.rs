@@ -0,0 +1,56 @@
+use std::env;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone)]
+pub struct RequestLocale(pub String);
+
+// The products and categories tables themselves hold the default locale
+#[derive(Debug, Clone)]
+pub struct LocaleConfig {
+    pub default: String,
+    pub supported: Vec<String>,
+}
+
+impl LocaleConfig {
+    pub fn from_env() -> Self {
+        let default = env::var("DEFAULT_LOCALE").unwrap_or_else(|_| "en".to_string()).to_lowercase();
+        let mut supported: Vec<String> = env::var("SUPPORTED_LOCALES")
+            .unwrap_or_else(|_| "en,ta,hi".to_string())
+            .split(',')
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        if !supported.contains(&default) {
+            supported.push(default.clone());
+        }
+        LocaleConfig { default, supported }
+    }
+
+    // An exact match, or else the primary language (ta-IN -> ta)
+    pub fn resolve(&self, tag: &str) -> Option<String> {
+        let tag = tag.trim().to_lowercase();
+        if self.supported.contains(&tag) {
+            return Some(tag);
+        }
+        let primary = tag.split('-').next()?;
+        self.supported.iter().find(|locale| locale.as_str() == primary).cloned()
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ProductTranslation {
+    pub locale: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductTranslationUpdate {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CategoryTranslationUpdate {
+    pub name: String,
+}
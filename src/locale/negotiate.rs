@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use actix_web::dev::Payload;
+use actix_web::http::header;
+use actix_web::{web, Error, FromRequest, HttpRequest};
+use futures::future::{ready, Ready};
+use crate::AppState;
+use crate::locale::models::{LocaleConfig, RequestLocale};
+
+impl FromRequest for RequestLocale {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let Some(state) = req.app_data::<web::Data<AppState>>() else {
+            return ready(Err(actix_web::error::ErrorInternalServerError("App state missing")));
+        };
+
+        let explicit = web::Query::<HashMap<String, String>>::from_query(req.query_string())
+            .ok()
+            .and_then(|query| query.get("lang").cloned());
+        let accept_language = req
+            .headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok());
+
+        ready(Ok(RequestLocale(negotiate(&state.locales, explicit.as_deref(), accept_language))))
+    }
+}
+
+// ?lang= if supported, then the best-weighted Accept-Language entry, then the default
+pub fn negotiate(config: &LocaleConfig, explicit: Option<&str>, accept_language: Option<&str>) -> String {
+    if let Some(locale) = explicit.and_then(|tag| config.resolve(tag)) {
+        return locale;
+    }
+
+    let mut ranges: Vec<(&str, f32)> = accept_language
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map(|q| q.trim().parse::<f32>().unwrap_or(0.0))
+                .unwrap_or(1.0);
+            (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+    // Stable, so equally weighted ranges keep the client's order
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    ranges
+        .into_iter()
+        .find_map(|(tag, _)| if tag == "*" { Some(config.default.clone()) } else { config.resolve(tag) })
+        .unwrap_or_else(|| config.default.clone())
+}
@@ -0,0 +1,45 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::category::models::Category;
+use crate::product::models::Product;
+
+// A translation without a description keeps the original one
+pub async fn translate_products(pool: &PgPool, products: &mut [Product], locale: &str) -> Result<(), sqlx::Error> {
+    let ids: Vec<Uuid> = products.iter().map(|p| p.id).collect();
+    let translations = sqlx::query_as::<_, (Uuid, String, Option<String>)>(
+        "SELECT product_id, name, description FROM product_translations \
+         WHERE product_id = ANY($1) AND locale = $2"
+    )
+        .bind(&ids)
+        .bind(locale)
+        .fetch_all(pool)
+        .await?;
+
+    for (product_id, name, description) in translations {
+        for product in products.iter_mut().filter(|p| p.id == product_id) {
+            product.name = name.clone();
+            if description.is_some() {
+                product.description = description.clone();
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn translate_categories(pool: &PgPool, categories: &mut [Category], locale: &str) -> Result<(), sqlx::Error> {
+    let ids: Vec<Uuid> = categories.iter().map(|c| c.id).collect();
+    let translations = sqlx::query_as::<_, (Uuid, String)>(
+        "SELECT category_id, name FROM category_translations WHERE category_id = ANY($1) AND locale = $2"
+    )
+        .bind(&ids)
+        .bind(locale)
+        .fetch_all(pool)
+        .await?;
+
+    for (category_id, name) in translations {
+        if let Some(category) = categories.iter_mut().find(|c| c.id == category_id) {
+            category.name = name;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+use ::log::info;
+use crate::mailer::{Email, MailError, Mailer};
+
+// Writes emails to the log instead of sending them, for development
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        info!("Email to {}: {}\n{}", email.to, email.subject, email.body);
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+pub mod log;
+pub mod smtp;
+
+use std::env;
+use std::sync::Arc;
+use async_trait::async_trait;
+
+#[derive(Debug)]
+pub struct MailError(pub String);
+
+impl std::fmt::Display for MailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Mail error: {}", self.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: &Email) -> Result<(), MailError>;
+}
+
+pub fn from_env() -> Arc<dyn Mailer> {
+    match env::var("MAILER").unwrap_or_else(|_| "log".to_string()).as_str() {
+        "smtp" => Arc::new(smtp::SmtpMailer::from_env()),
+        _ => Arc::new(self::log::LogMailer),
+    }
+}
+
+pub fn app_base_url() -> String {
+    env::var("APP_BASE_URL")
+        .unwrap_or_else(|_| "http://localhost:8080".to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
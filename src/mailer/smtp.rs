@@ -0,0 +1,43 @@
+use std::env;
+use async_trait::async_trait;
+use lettre::message::Mailbox;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use crate::mailer::{Email, MailError, Mailer};
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn from_env() -> Self {
+        let url = env::var("SMTP_URL").expect("SMTP_URL must be set when MAILER=smtp");
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(&url)
+            .expect("SMTP_URL must be a valid SMTP URL")
+            .build();
+        let from = env::var("MAIL_FROM")
+            .expect("MAIL_FROM must be set when MAILER=smtp")
+            .parse()
+            .expect("MAIL_FROM must be a valid mailbox");
+        Self { transport, from }
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        let to: Mailbox = email.to.parse().map_err(|e| MailError(format!("Bad recipient {}: {}", email.to, e)))?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject.as_str())
+            .body(email.body.clone())
+            .map_err(|e| MailError(e.to_string()))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| MailError(e.to_string()))
+    }
+}
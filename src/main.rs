@@ -4,6 +4,7 @@ use std::env;
 use dotenv::dotenv;
 use env_logger::{init, Env};
 use sqlx::PgPool;
+use std::sync::Arc;
 use stripe::Client;
 
 mod db;
@@ -13,6 +14,26 @@ mod product;
 mod cart;
 mod order;
 mod models;
+mod inventory;
+mod storage;
+mod review;
+mod money;
+mod pricing;
+mod catalogue;
+mod category;
+mod http_cache;
+mod cache;
+mod wishlist;
+mod mailer;
+mod alert;
+mod bundle;
+mod locale;
+mod digital;
+mod recently_viewed;
+mod question;
+mod coupon;
+mod promotion;
+mod cart_recovery;
 
 use db::pool::init_db_pool;
 
@@ -20,7 +41,15 @@ use db::pool::init_db_pool;
 struct AppState {
     db_pool: PgPool,
     stripe_client: Client,
-    stripe_secret: String
+    stripe_secret: String,
+    stripe_webhook_secret: String,
+    blob_store: Arc<dyn storage::BlobStore>,
+    base_currency: money::Currency,
+    cache_policies: http_cache::models::CachePolicies,
+    catalogue_cache: Arc<cache::CatalogueCache>,
+    locales: locale::models::LocaleConfig,
+    recently_viewed: recently_viewed::recorder::ViewRecorder,
+    cart_charges: cart::models::CartCharges,
 }
 
 #[actix_web::main]
@@ -34,14 +63,45 @@ async fn main() -> std::io::Result<()> {
     
     let db_pool = init_db_pool(&database_url).await.expect("Failed to create pool");
 
+    // `import` / `export` run the catalogue CLI instead of the server
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        return catalogue::cli::run(&db_pool, &args).await;
+    }
+
     let stripe_secret = env::var("STRIPE_SECRET").unwrap();
     let stripe_client = stripe::Client::new(&stripe_secret);
+    let stripe_webhook_secret = env::var("STRIPE_WEBHOOK_SECRET").expect("STRIPE_WEBHOOK_SECRET must be set");
+
+    let base_currency = env::var("BASE_CURRENCY")
+        .unwrap_or_else(|_| "inr".to_string())
+        .parse::<money::Currency>()
+        .expect("BASE_CURRENCY must be a supported currency");
+
+    inventory::reservation::spawn_expiry_job(db_pool.clone());
+    product::related::spawn_refresh_job(db_pool.clone());
+
+    let mailer = mailer::from_env();
+    alert::notify::spawn_alert_job(db_pool.clone(), mailer.clone(), base_currency);
+    cart_recovery::reminders::spawn_recovery_job(db_pool.clone(), mailer);
+
+    let catalogue_cache = cache::from_env().await;
+    cache::listener::spawn_invalidation_listener(db_pool.clone(), catalogue_cache.clone());
 
+    let recently_viewed = recently_viewed::recorder::spawn_view_recorder(db_pool.clone());
 
     let app_state = AppState {
         db_pool,
         stripe_client,
         stripe_secret,
+        stripe_webhook_secret,
+        blob_store: storage::from_env(),
+        base_currency,
+        cache_policies: http_cache::models::CachePolicies::from_env(),
+        catalogue_cache,
+        locales: locale::models::LocaleConfig::from_env(),
+        recently_viewed,
+        cart_charges: cart::models::CartCharges::from_env(),
     };
 
     HttpServer::new(move || {
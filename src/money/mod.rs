@@ -0,0 +1,160 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use crate::pricing::models::PricingError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Currency {
+    Inr,
+    Usd,
+    Eur,
+    Gbp,
+}
+
+impl Currency {
+    // Lowercase ISO 4217 code, as stored in SQL and sent to Stripe
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Currency::Inr => "inr",
+            Currency::Usd => "usd",
+            Currency::Eur => "eur",
+            Currency::Gbp => "gbp",
+        }
+    }
+
+    pub fn minor_per_major(&self) -> i64 {
+        100
+    }
+
+    pub fn to_stripe(self) -> stripe::Currency {
+        match self {
+            Currency::Inr => stripe::Currency::INR,
+            Currency::Usd => stripe::Currency::USD,
+            Currency::Eur => stripe::Currency::EUR,
+            Currency::Gbp => stripe::Currency::GBP,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownCurrency(pub String);
+
+impl fmt::Display for UnknownCurrency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown currency: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownCurrency {}
+
+impl FromStr for Currency {
+    type Err = UnknownCurrency;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "inr" => Ok(Currency::Inr),
+            "usd" => Ok(Currency::Usd),
+            "eur" => Ok(Currency::Eur),
+            "gbp" => Ok(Currency::Gbp),
+            _ => Err(UnknownCurrency(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// Minor units in a BIGINT column, never floating point
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Money {
+    pub minor_units: i64,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(minor_units: i64, currency: Currency) -> Self {
+        Self { minor_units, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(0, currency)
+    }
+
+    pub fn times(self, quantity: i64) -> Result<Self, PricingError> {
+        let minor_units = self.minor_units.checked_mul(quantity).ok_or(PricingError::Overflow)?;
+        Ok(Self::new(minor_units, self.currency))
+    }
+
+    pub fn checked_add(self, other: Money) -> Result<Self, PricingError> {
+        let minor_units = self.same_currency(other)?.checked_add(other.minor_units).ok_or(PricingError::Overflow)?;
+        Ok(Self::new(minor_units, self.currency))
+    }
+
+    pub fn checked_sub(self, other: Money) -> Result<Self, PricingError> {
+        let minor_units = self.same_currency(other)?.checked_sub(other.minor_units).ok_or(PricingError::Overflow)?;
+        Ok(Self::new(minor_units, self.currency))
+    }
+
+    // numerator / denominator of the amount, rounding halves up
+    pub fn share(self, numerator: i64, denominator: i64) -> Result<Self, PricingError> {
+        let scaled = (self.minor_units as i128 * numerator as i128 + denominator as i128 / 2) / denominator as i128;
+        let minor_units = i64::try_from(scaled).map_err(|_| PricingError::Overflow)?;
+        Ok(Self::new(minor_units, self.currency))
+    }
+
+    fn same_currency(self, other: Money) -> Result<i64, PricingError> {
+        if self.currency != other.currency {
+            return Err(PricingError::CurrencyMismatch { left: self.currency, right: other.currency });
+        }
+        Ok(self.minor_units)
+    }
+
+    pub fn from_columns(row: &PgRow, amount: &str, currency: &str) -> Result<Self, sqlx::Error> {
+        let minor_units: i64 = row.try_get(amount)?;
+        let code: String = row.try_get(currency)?;
+        let currency = code.parse::<Currency>().map_err(|e| sqlx::Error::ColumnDecode {
+            index: currency.to_string(),
+            source: Box::new(e),
+        })?;
+        Ok(Self::new(minor_units, currency))
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let per_major = self.currency.minor_per_major();
+        let sign = if self.minor_units < 0 { "-" } else { "" };
+        let abs = self.minor_units.abs();
+        write!(f, "{}{}.{:02} {}", sign, abs / per_major, abs % per_major, self.currency.as_str().to_uppercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn times_reports_overflow() {
+        let price = Money::new(i64::MAX / 2, Currency::Inr);
+        assert_eq!(price.times(2).unwrap().minor_units, i64::MAX - 1);
+        assert!(matches!(price.times(3), Err(PricingError::Overflow)));
+    }
+
+    #[test]
+    fn adding_other_currencies_is_an_error() {
+        let sum = Money::new(100, Currency::Inr).checked_add(Money::new(100, Currency::Usd));
+        assert!(matches!(sum, Err(PricingError::CurrencyMismatch { left: Currency::Inr, right: Currency::Usd })));
+    }
+
+    #[test]
+    fn share_rounds_halves_up() {
+        assert_eq!(Money::new(250, Currency::Inr).share(1_800, 10_000).unwrap().minor_units, 45);
+        assert_eq!(Money::new(25, Currency::Inr).share(10, 100).unwrap().minor_units, 3);
+    }
+}
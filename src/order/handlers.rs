@@ -1,65 +1,74 @@
 use crate::AppState;
 use crate::auth::models::AuthenticatedUser;
-use crate::order::models::CheckOut;
+use crate::inventory::models::ReservationError;
+use crate::money::Money;
+use crate::inventory::reservation::{commit_for_order, reclaim_for_order, release_for_order, reservation_ttl, reserve_for_order};
+use crate::bundle::components::expand_bundles;
+use crate::cart::pricing::price_cart;
+use crate::cart_recovery::reminders::record_recovery;
+use crate::coupon::engine::redeem;
+use crate::digital::grants::{download_links, grant_downloads};
+use crate::order::models::{CheckOut, Order, OrderItem, OrderLine, OrderSummary};
 use crate::order::payment::PaymentService;
+use crate::pricing::models::{PricingError, RequestCurrency};
 use crate::routes::models::ApiResponse;
-use actix_web::{HttpResponse, Responder, post, web};
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use log::{error, warn};
 use serde_json::json;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use stripe::{EventObject, EventType, Webhook};
 use uuid::Uuid;
 
 pub async fn create_checkout(
     data: web::Data<AppState>,
     order: web::Json<CheckOut>,
     user: AuthenticatedUser,
+    currency: RequestCurrency,
 ) -> impl Responder {
-    // Step 1: Fetch cart items
-    let cart_items = match sqlx::query!(
-        r#"
-        SELECT ci.quantity, p.name, p.price
-        FROM cart_items ci
-        JOIN products p ON p.id = ci.product_id
-        WHERE ci.cart_id = $1
-        "#,
-        order.cart_id
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uid) => uid,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(format!("Invalid user ID in claims: {}", e));
+        }
+    };
+
+    // Step 1: Check the cart belongs to the user
+    let owned = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM shopping_cart WHERE id = $1 AND user_id = $2)"
     )
-        .fetch_all(&data.db_pool)
-        .await
-    {
-        Ok(items) => items,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(ApiResponse {
+        .bind(order.cart_id)
+        .bind(user_id)
+        .fetch_one(&data.db_pool)
+        .await;
+
+    match owned {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::NotFound().json(ApiResponse {
                 status: "Error".to_string(),
-                msg: "Failed to fetch cart items".to_string(),
+                msg: "Cart not found".to_string(),
                 data: json!({}),
             });
         }
-    };
-
-    // Step 2: Prepare Stripe items and calculate total amount
-    let mut stripe_items = vec![];
-    let mut total_amount: i64 = 0;
-
-    for item in cart_items {
-        // Ensure name, price, and quantity are not null
-        let name = item.name; 
-        
-        let price =  item.price;
-
-        let quantity = item.quantity;
-
-        // Stripe works with amounts in cents (i.e., 100 = ₹1.00)
-        let item_total = (price * quantity as f64 * 100.0).round() as i64;
-        total_amount += item_total;
-
-        stripe_items.push((
-            name.clone(),  // clone String from Option<String>
-            (price * 100.0).round() as i64, // price in cents
-            quantity,
-        ));
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
     }
 
+    // Step 2: Price the cart in the requested currency, exactly as the cart view shows it
+    let priced = match price_cart(&data, order.cart_id, currency.0, &data.locales.default).await {
+        Ok(priced) => priced,
+        Err(e @ (PricingError::Unpriceable { .. } | PricingError::NoRate { .. } | PricingError::Overflow)) => {
+            return HttpResponse::BadRequest().json(ApiResponse {
+                status: "Error".to_string(),
+                msg: e.to_string(),
+                data: json!({}),
+            });
+        }
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
 
-    if stripe_items.is_empty() {
+    if priced.lines.is_empty() {
         return HttpResponse::BadRequest().json(ApiResponse {
             status: "Error".to_string(),
             msg: "Cart is empty or contains invalid items".to_string(),
@@ -67,49 +76,335 @@ pub async fn create_checkout(
         });
     }
 
-    // Step 3: Create Stripe checkout session
+    // Step 3: Prepare Stripe items; tax and shipping are lines of their own
+    let mut stripe_items = vec![];
+    let mut lines = vec![];
+    for line in &priced.lines {
+        stripe_items.push((line.product.product.name.clone(), line.unit_price, line.quantity));
+        lines.push(OrderLine {
+            product_id: line.product.product.id,
+            quantity: line.quantity,
+            price: line.unit_price,
+            bundle_id: None,
+        });
+    }
+    if priced.shipping.minor_units > 0 {
+        stripe_items.push(("Shipping".to_string(), priced.shipping, 1));
+    }
+    if priced.tax.minor_units > 0 {
+        stripe_items.push(("Tax".to_string(), priced.tax, 1));
+    }
+    let total_amount = priced.total;
+
+    // Bundles are sold as one Stripe line but ordered and reserved as their components
+    let lines = match expand_bundles(&data.db_pool, data.base_currency, currency.0, lines).await {
+        Ok(lines) => lines,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    // Step 4: Insert the pending order and reserve its stock in one transaction
+    let expires_at = chrono::Utc::now() + reservation_ttl();
+    let order_id = match place_order(&data.db_pool, user_id, total_amount, &lines, expires_at).await {
+        Ok(order_id) => order_id,
+        Err(ReservationError::InsufficientStock { product_id }) => {
+            return HttpResponse::Conflict().json(ApiResponse {
+                status: "Error".to_string(),
+                msg: "Not enough stock for an item in the cart".to_string(),
+                data: json!({ "product_id": product_id }),
+            });
+        }
+        Err(e) => return HttpResponse::InternalServerError().body(format!("DB Insert error: {}", e)),
+    };
+
+    // Step 5: Hold a use of the coupon for the pending order
+    if let Some((coupon_id, coupon_discount)) = priced.coupon_redemption {
+        let redeemed = match data.db_pool.begin().await {
+            Ok(mut tx) => match redeem(&mut tx, coupon_id, user_id, order_id, coupon_discount).await {
+                Ok(redeemed) => tx.commit().await.map(|_| redeemed),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        match redeemed {
+            Ok(true) => {}
+            Ok(false) => {
+                if let Err(db_err) = close_order(&data.db_pool, order_id, "failed").await {
+                    error!("Failed to release stock for order {}: {}", order_id, db_err);
+                }
+                return HttpResponse::Conflict().json(ApiResponse {
+                    status: "Error".to_string(),
+                    msg: "The coupon on this cart can no longer be used".to_string(),
+                    data: json!({}),
+                });
+            }
+            Err(e) => {
+                if let Err(db_err) = close_order(&data.db_pool, order_id, "failed").await {
+                    error!("Failed to release stock for order {}: {}", order_id, db_err);
+                }
+                return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+            }
+        }
+    }
+
+    // Step 6: Create Stripe checkout session
     let payment_service = PaymentService::new(&data.stripe_secret);
     let session_result = payment_service
         .create_checkout_session(
+            order_id,
             stripe_items,
             "http://localhost:8080/success".to_string(),
             "http://localhost:8080/cancel".to_string(),
+            expires_at,
+            Some(priced.discount_total).filter(|discount| discount.minor_units > 0),
         )
         .await;
 
     match session_result {
-        Ok(session_url) => {
-            // Step 4: Insert into orders table
-            let user_id = match Uuid::parse_str(&user.claims.sub) {
-                Ok(uid) => uid,
-                Err(e) => {
-                    return HttpResponse::InternalServerError()
-                        .body(format!("Invalid user ID in claims: {}", e));
-                }
-            };
-
-            if let Err(e) = sqlx::query!(
-                r#"
-                INSERT INTO orders (user_id, payment_id, status, total_amount, currency)
-                VALUES ($1, $2, 'pending', $3, 'usd')
-                "#,
-                user_id,
-                session_url.clone(), // Store the session URL or session ID if available
-                total_amount
-            )
+        Ok(session) => {
+            // Step 7: Link the order to the Stripe session
+            if let Err(e) = sqlx::query("UPDATE orders SET payment_id = $1 WHERE id = $2")
+                .bind(session.id.as_str())
+                .bind(order_id)
                 .execute(&data.db_pool)
                 .await
             {
                 return HttpResponse::InternalServerError().body(format!("DB Insert error: {}", e));
             }
 
-            // Step 5: Respond with session URL
+            // Step 8: Respond with session URL
             HttpResponse::Ok().json(ApiResponse {
                 status: "Success".to_string(),
                 msg: "Redirect the URL to pay".to_string(),
-                data: json!({ "checkout_url": session_url }),
+                data: json!({ "checkout_url": session.url, "order_id": order_id }),
             })
         }
-        Err(e) => HttpResponse::InternalServerError().body(format!("Stripe error: {}", e)),
+        Err(e) => {
+            if let Err(db_err) = close_order(&data.db_pool, order_id, "failed").await {
+                error!("Failed to release stock for order {}: {}", order_id, db_err);
+            }
+            HttpResponse::InternalServerError().body(format!("Stripe error: {}", e))
+        }
+    }
+}
+
+async fn place_order(
+    pool: &PgPool,
+    user_id: Uuid,
+    total_amount: Money,
+    lines: &[OrderLine],
+    expires_at: chrono::DateTime<chrono::Utc>,
+) -> Result<Uuid, ReservationError> {
+    let mut tx = pool.begin().await?;
+
+    let order_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO orders (user_id, payment_id, status, total_amount, currency)
+        VALUES ($1, '', 'pending', $2, $3)
+        RETURNING id
+        "#
+    )
+        .bind(user_id)
+        .bind(total_amount.minor_units)
+        .bind(total_amount.currency.as_str())
+        .fetch_one(&mut *tx)
+        .await?;
+
+    for line in lines {
+        sqlx::query(
+            "INSERT INTO order_items (order_id, product_id, quantity, price, currency, bundle_id) VALUES ($1, $2, $3, $4, $5, $6)"
+        )
+            .bind(order_id)
+            .bind(line.product_id)
+            .bind(line.quantity)
+            .bind(line.price.minor_units)
+            .bind(line.price.currency.as_str())
+            .bind(line.bundle_id)
+            .execute(&mut *tx)
+            .await?;
     }
+
+    let reservations: Vec<(Uuid, i32)> = lines.iter().map(|line| (line.product_id, line.quantity)).collect();
+    reserve_for_order(&mut tx, order_id, &reservations, expires_at).await?;
+
+    tx.commit().await?;
+    Ok(order_id)
+}
+
+async fn close_order(pool: &PgPool, order_id: Uuid, status: &str) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE orders SET status = $1 WHERE id = $2 AND status = 'pending'")
+        .bind(status)
+        .bind(order_id)
+        .execute(&mut *tx)
+        .await?;
+    release_for_order(&mut tx, order_id).await?;
+    // An unpaid order gives its coupon use back
+    sqlx::query(
+        "DELETE FROM coupon_redemptions r USING orders o \
+         WHERE r.order_id = o.id AND o.id = $1 AND o.status <> 'paid'"
+    )
+        .bind(order_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
+
+pub async fn stripe_webhook(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    payload: web::Bytes,
+) -> impl Responder {
+    let signature = match req.headers().get("Stripe-Signature").and_then(|v| v.to_str().ok()) {
+        Some(sig) => sig,
+        None => return HttpResponse::BadRequest().body("Missing Stripe-Signature header"),
+    };
+
+    let body = match std::str::from_utf8(&payload) {
+        Ok(body) => body,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid payload"),
+    };
+
+    let event = match Webhook::construct_event(body, signature, &data.stripe_webhook_secret) {
+        Ok(event) => event,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Invalid webhook: {}", e)),
+    };
+
+    let session = match event.data.object {
+        EventObject::CheckoutSession(session) => session,
+        _ => return HttpResponse::Ok().finish(),
+    };
+
+    // The order id travels with the session, so this works even if saving payment_id failed
+    let Some(order_id) = session.client_reference_id.as_deref().and_then(|id| Uuid::parse_str(id).ok()) else {
+        warn!("Webhook for checkout session {} without an order reference", session.id);
+        return HttpResponse::Ok().finish();
+    };
+
+    let order_id: Uuid = match sqlx::query_scalar("SELECT id FROM orders WHERE id = $1")
+        .bind(order_id)
+        .fetch_optional(&data.db_pool)
+        .await
+    {
+        Ok(Some(order_id)) => order_id,
+        Ok(None) => {
+            warn!("Webhook for unknown checkout session {}", session.id);
+            return HttpResponse::Ok().finish();
+        }
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let result = match event.type_ {
+        EventType::CheckoutSessionCompleted => mark_order_paid(&data.db_pool, order_id).await,
+        EventType::CheckoutSessionExpired => close_order(&data.db_pool, order_id, "expired").await,
+        _ => Ok(()),
+    };
+
+    match result {
+        Ok(_) => HttpResponse::Ok().finish(),
+        // A non-2xx response makes Stripe retry the delivery later.
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+async fn mark_order_paid(pool: &PgPool, order_id: Uuid) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let status: Option<String> = sqlx::query_scalar("SELECT status FROM orders WHERE id = $1 FOR UPDATE")
+        .bind(order_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    match status.as_deref() {
+        Some("pending") => {}
+        // Stripe may deliver the same event more than once.
+        Some("paid") | Some("needs_review") | None => return tx.commit().await,
+        // Paid after the reservation lapsed: take the stock back if it is still free
+        Some(status) => match reclaim_for_order(&mut tx, order_id).await {
+            Ok(()) => warn!("Order {} was paid after being marked {}; stock reclaimed", order_id, status),
+            Err(ReservationError::InsufficientStock { product_id }) => {
+                error!(
+                    "Order {} was paid after being marked {} but product {} is no longer in stock; needs a refund or manual review",
+                    order_id, status, product_id
+                );
+                drop(tx);
+                sqlx::query("UPDATE orders SET status = 'needs_review' WHERE id = $1")
+                    .bind(order_id)
+                    .execute(pool)
+                    .await?;
+                return Ok(());
+            }
+            Err(ReservationError::Db(e)) => return Err(e),
+        },
+    }
+
+    sqlx::query("UPDATE orders SET status = 'paid' WHERE id = $1")
+        .bind(order_id)
+        .execute(&mut *tx)
+        .await?;
+
+    commit_for_order(&mut tx, order_id).await?;
+    grant_downloads(&mut tx, order_id).await?;
+    record_recovery(&mut tx, order_id).await?;
+
+    tx.commit().await
+}
+
+pub async fn get_my_orders(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let orders = sqlx::query_as::<_, Order>("SELECT * FROM orders WHERE user_id = $1 ORDER BY created_at DESC")
+        .bind(user_id)
+        .fetch_all(&data.db_pool)
+        .await;
+
+    let orders = match orders {
+        Ok(orders) => orders,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let order_ids: Vec<Uuid> = orders.iter().map(|o| o.id).collect();
+    let items = sqlx::query_as::<_, OrderItem>(
+        "SELECT * FROM order_items WHERE order_id = ANY($1) ORDER BY bundle_id NULLS FIRST, id"
+    )
+        .bind(&order_ids)
+        .fetch_all(&data.db_pool)
+        .await;
+
+    let items = match items {
+        Ok(items) => items,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let mut downloads = match download_links(&data.db_pool, user_id).await {
+        Ok(downloads) => downloads,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let mut items_by_order: HashMap<Uuid, Vec<OrderItem>> = HashMap::new();
+    for item in items {
+        items_by_order.entry(item.order_id).or_default().push(item);
+    }
+
+    let summaries: Vec<OrderSummary> = orders
+        .into_iter()
+        .map(|order| OrderSummary {
+            items: items_by_order.remove(&order.id).unwrap_or_default(),
+            downloads: downloads.remove(&order.id).unwrap_or_default(),
+            order,
+        })
+        .collect();
+
+    HttpResponse::Ok().json(ApiResponse {
+        status: "Success".to_string(),
+        msg: "Your orders".to_string(),
+        data: summaries,
+    })
 }
@@ -1,7 +1,10 @@
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use sqlx::FromRow;
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, Row};
+use crate::digital::models::DownloadLink;
+use crate::money::Money;
 use chrono::NaiveDateTime;
 
 
@@ -11,22 +14,66 @@ pub struct CheckOut {
 }
 
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Order {
     pub id: Uuid,
     pub user_id: Uuid,
     pub payment_id: String,
     pub status: String,
-    pub total_amount: i64,
-    pub currency: String,
+    pub total_amount: Money,
     pub created_at: NaiveDateTime,
 }
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+impl<'r> FromRow<'r, PgRow> for Order {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Order {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            payment_id: row.try_get("payment_id")?,
+            status: row.try_get("status")?,
+            total_amount: Money::from_columns(row, "total_amount", "currency")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct OrderItem {
     pub id: Uuid,
     pub order_id: Uuid,
     pub product_id: Uuid,
     pub quantity: i32,
-    pub price: i64,
+    pub price: Money,
+    pub bundle_id: Option<Uuid>,
+}
+
+impl<'r> FromRow<'r, PgRow> for OrderItem {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(OrderItem {
+            id: row.try_get("id")?,
+            order_id: row.try_get("order_id")?,
+            product_id: row.try_get("product_id")?,
+            quantity: row.try_get("quantity")?,
+            price: Money::from_columns(row, "price", "currency")?,
+            bundle_id: row.try_get("bundle_id")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OrderLine {
+    pub product_id: Uuid,
+    pub quantity: i32,
+    pub price: Money,
+    // Set on component lines sold as part of a bundle
+    pub bundle_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderSummary {
+    #[serde(flatten)]
+    pub order: Order,
+    pub items: Vec<OrderItem>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub downloads: Vec<DownloadLink>,
 }
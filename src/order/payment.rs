@@ -1,7 +1,8 @@
-use stripe::{CheckoutSessionPaymentMethodOptions, Client, CreateCheckoutSession, CreateCheckoutSessionLineItems, CreateCheckoutSessionLineItemsPriceData, CreateCheckoutSessionLineItemsPriceDataProductData, CreateCheckoutSessionPaymentMethodOptions, CreateCheckoutSessionPaymentMethodTypes, Currency};
+use stripe::{CheckoutSession, Client, Coupon, CouponDuration, CreateCheckoutSession, CreateCheckoutSessionDiscounts, CreateCoupon, CreateCheckoutSessionLineItems, CreateCheckoutSessionLineItemsPriceData, CreateCheckoutSessionLineItemsPriceDataProductData, CreateCheckoutSessionPaymentMethodTypes};
 use uuid::Uuid;
 use stripe::CheckoutSessionMode;
-use stripe::PaymentMethodType;
+use chrono::{DateTime, Utc};
+use crate::money::Money;
 
 pub struct PaymentService {
     client: Client,
@@ -16,39 +17,68 @@ impl PaymentService {
 
     pub async fn create_checkout_session(
         &self,
-        items: Vec<(String, i64, i32)>, // (name, price in cents, quantity)
+        order_id: Uuid,
+        items: Vec<(String, Money, i32)>, // (name, unit price, quantity)
         success_url: String,
         cancel_url: String,
-    ) -> Result<String, stripe::StripeError> {
+        expires_at: DateTime<Utc>,
+        discount: Option<Money>,
+    ) -> Result<CheckoutSession, stripe::StripeError> {
         let line_items: Vec<CreateCheckoutSessionLineItems> = items
             .into_iter()
             .map(|(name, price, quantity)| CreateCheckoutSessionLineItems {
                 price_data: Some(CreateCheckoutSessionLineItemsPriceData {
-                    currency: Currency::INR,
-                    unit_amount: Some(price),
+                    currency: price.currency.to_stripe(),
+                    unit_amount: Some(price.minor_units),
                     product_data: Some(CreateCheckoutSessionLineItemsPriceDataProductData {
                         name,
                         ..Default::default()
                     }),
                     ..Default::default()
                 }),
-                quantity: Some(quantity as u64),
+                quantity: u64::try_from(quantity).ok(),
                 ..Default::default()
             })
             .collect();
 
+        let order_ref = order_id.to_string();
+
+        // Discounts are already worked out by the cart, so Stripe gets a
+        // single-use coupon for exactly that amount
+        let discounts = match discount {
+            Some(discount) => {
+                let name = format!("Order {}", order_ref);
+                let coupon = Coupon::create(&self.client, CreateCoupon {
+                    amount_off: Some(discount.minor_units),
+                    currency: Some(discount.currency.to_stripe()),
+                    duration: Some(CouponDuration::Once),
+                    max_redemptions: Some(1),
+                    name: Some(name.as_str()),
+                    ..Default::default()
+                }).await?;
+                Some(vec![CreateCheckoutSessionDiscounts {
+                    coupon: Some(coupon.id.to_string()),
+                    ..Default::default()
+                }])
+            }
+            None => None,
+        };
+
         let session = CreateCheckoutSession {
             success_url: Some(success_url.as_str()),
             cancel_url: Some(cancel_url.as_str()),
+            client_reference_id: Some(order_ref.as_str()),
+            // Let the session die together with the stock reservation.
+            expires_at: Some(expires_at.timestamp()),
             payment_method_types : Some(vec![
                 CreateCheckoutSessionPaymentMethodTypes::Card,
             ]),
             mode: Some(CheckoutSessionMode::Payment),
             line_items: Some(line_items),
+            discounts,
             ..Default::default()
         };
 
-        let checkout = stripe::CheckoutSession::create(&self.client, session).await?;
-        Ok(checkout.url.unwrap_or_else(|| "no url".to_string()))
+        stripe::CheckoutSession::create(&self.client, session).await
     }
 }
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use actix_web::dev::Payload;
+use actix_web::{web, Error, FromRequest, HttpRequest};
+use futures::future::LocalBoxFuture;
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::AuthenticatedUser;
+use crate::money::Currency;
+use crate::pricing::models::RequestCurrency;
+
+impl FromRequest for RequestCurrency {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let state = req.app_data::<web::Data<AppState>>().cloned();
+
+        let explicit = web::Query::<HashMap<String, String>>::from_query(req.query_string())
+            .ok()
+            .and_then(|query| query.get("currency").cloned())
+            .or_else(|| {
+                req.headers()
+                    .get("X-Currency")
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string)
+            });
+
+        let user_id = AuthenticatedUser::from_request(req, payload)
+            .into_inner()
+            .ok()
+            .and_then(|user| Uuid::parse_str(&user.claims.sub).ok());
+
+        Box::pin(async move {
+            let state = state.ok_or_else(|| actix_web::error::ErrorInternalServerError("App state missing"))?;
+
+            if let Some(code) = explicit {
+                return code
+                    .parse::<Currency>()
+                    .map(RequestCurrency)
+                    .map_err(actix_web::error::ErrorBadRequest);
+            }
+
+            if let Some(user_id) = user_id {
+                let preferred = sqlx::query_scalar::<_, Option<String>>(
+                    "SELECT preferred_currency FROM users WHERE id = $1"
+                )
+                    .bind(user_id)
+                    .fetch_optional(&state.db_pool)
+                    .await
+                    .map_err(actix_web::error::ErrorInternalServerError)?;
+
+                if let Some(Ok(currency)) = preferred.flatten().map(|code| code.parse::<Currency>()) {
+                    return Ok(RequestCurrency(currency));
+                }
+            }
+
+            Ok(RequestCurrency(state.base_currency))
+        })
+    }
+}
@@ -0,0 +1,200 @@
+use actix_web::{web, HttpResponse, Responder};
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::{AdminUser, AuthenticatedUser};
+use crate::money::Currency;
+use crate::pricing::models::{CurrencyPreference, ExchangeRateUpdate, NewSale, PriceUpdate, ProductSale};
+use crate::routes::models::ApiResponse;
+
+pub async fn set_exchange_rate(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    currency: web::Path<Currency>,
+    payload: web::Json<ExchangeRateUpdate>,
+) -> impl Responder {
+    let currency = currency.into_inner();
+
+    let rate = payload.units_per_base;
+    if currency == data.base_currency || rate.is_nan() || rate <= 0.0 {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Rate must be positive and cannot be set for the base currency".to_string(),
+            data: "No Data".to_string(),
+        });
+    }
+
+    let result = sqlx::query(
+        "INSERT INTO exchange_rates (currency, units_per_base) VALUES ($1, $2::float8::numeric) \
+         ON CONFLICT (currency) DO UPDATE SET units_per_base = EXCLUDED.units_per_base, updated_at = now()"
+    )
+        .bind(currency.as_str())
+        .bind(rate)
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Exchange rate updated".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn set_product_price(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    path: web::Path<(Uuid, Currency)>,
+    payload: web::Json<PriceUpdate>,
+) -> impl Responder {
+    let (product_id, currency) = path.into_inner();
+
+    if payload.minor_units < 0 {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Price cannot be negative".to_string(),
+            data: "No Data".to_string(),
+        });
+    }
+
+    let result = sqlx::query(
+        "INSERT INTO product_prices (product_id, currency, amount) VALUES ($1, $2, $3) \
+         ON CONFLICT (product_id, currency) DO UPDATE SET amount = EXCLUDED.amount"
+    )
+        .bind(product_id)
+        .bind(currency.as_str())
+        .bind(payload.minor_units)
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Price list updated".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn set_currency_preference(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    payload: web::Json<CurrencyPreference>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let result = sqlx::query("UPDATE users SET preferred_currency = $1 WHERE id = $2")
+        .bind(payload.currency.as_str())
+        .bind(user_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Currency preference saved".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn create_sale(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    product_id: web::Path<Uuid>,
+    payload: web::Json<NewSale>,
+) -> impl Responder {
+    if payload.sale_price < 0 || payload.ends_at <= payload.starts_at {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Sale price cannot be negative and the sale must end after it starts".to_string(),
+            data: "No Data".to_string(),
+        });
+    }
+
+    let sale = sqlx::query_as::<_, ProductSale>(
+        "INSERT INTO product_sales (product_id, sale_price, starts_at, ends_at) \
+         VALUES ($1, $2, $3, $4) RETURNING *"
+    )
+        .bind(product_id.into_inner())
+        .bind(payload.sale_price)
+        .bind(payload.starts_at)
+        .bind(payload.ends_at)
+        .fetch_one(&data.db_pool)
+        .await;
+
+    match sale {
+        Ok(sale) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Sale scheduled".to_string(),
+            data: sale,
+        }),
+        Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn get_sales(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    product_id: web::Path<Uuid>,
+) -> impl Responder {
+    let sales = sqlx::query_as::<_, ProductSale>(
+        "SELECT * FROM product_sales WHERE product_id = $1 ORDER BY starts_at"
+    )
+        .bind(product_id.into_inner())
+        .fetch_all(&data.db_pool)
+        .await;
+
+    match sales {
+        Ok(sales) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Sales fetched".to_string(),
+            data: sales,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn delete_sale(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    path: web::Path<(Uuid, Uuid)>,
+) -> impl Responder {
+    let (product_id, sale_id) = path.into_inner();
+
+    let result = sqlx::query("DELETE FROM product_sales WHERE id = $1 AND product_id = $2")
+        .bind(sale_id)
+        .bind(product_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(done) if done.rows_affected() == 0 => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Sale not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Sale removed".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
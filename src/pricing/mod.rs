@@ -0,0 +1,4 @@
+pub mod currency;
+pub mod handlers;
+pub mod models;
+pub mod rates;
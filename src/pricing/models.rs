@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::money::{Currency, Money};
+
+// From ?currency=, the X-Currency header, the user's preference, then the base currency
+pub struct RequestCurrency(pub Currency);
+
+#[derive(Debug, Clone, Copy)]
+pub struct PriceQuote {
+    pub price: Money,
+    pub compare_at_price: Option<Money>,
+    pub lowest_price_30d: Option<Money>,
+    pub sale_ends_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+pub enum PricingError {
+    Unpriceable { product_id: Uuid, currency: Currency },
+    NoRate { currency: Currency },
+    Overflow,
+    CurrencyMismatch { left: Currency, right: Currency },
+    Db(sqlx::Error),
+}
+
+impl std::fmt::Display for PricingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PricingError::Unpriceable { product_id, currency } => {
+                write!(f, "Product {} has no price in {}", product_id, currency)
+            }
+            PricingError::NoRate { currency } => write!(f, "No exchange rate for {}", currency),
+            PricingError::Overflow => write!(f, "Amount is too large"),
+            PricingError::CurrencyMismatch { left, right } => {
+                write!(f, "Cannot combine amounts in {} and {}", left, right)
+            }
+            PricingError::Db(e) => write!(f, "DB error: {}", e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for PricingError {
+    fn from(e: sqlx::Error) -> Self {
+        PricingError::Db(e)
+    }
+}
+
+// units_per_base (a NUMERIC(20, 10)) times 10^10, kept exact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaseRate {
+    pub currency: Currency,
+    pub scaled: Option<i128>,
+}
+
+impl BaseRate {
+    pub const SCALE: i128 = 10_000_000_000;
+
+    pub fn identity(currency: Currency) -> Self {
+        Self { currency, scaled: Some(Self::SCALE) }
+    }
+
+    // Rounds half away from zero, like ROUND on NUMERIC
+    pub fn convert(&self, minor_units: i64) -> Result<Money, PricingError> {
+        let Some(scaled) = self.scaled else {
+            return Err(PricingError::NoRate { currency: self.currency });
+        };
+        let product = (minor_units as i128).checked_mul(scaled).ok_or(PricingError::Overflow)?;
+        let half = Self::SCALE / 2;
+        let rounded = if product < 0 { (product - half) / Self::SCALE } else { (product + half) / Self::SCALE };
+        let rounded = i64::try_from(rounded).map_err(|_| PricingError::Overflow)?;
+        Ok(Money::new(rounded, self.currency))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExchangeRateUpdate {
+    pub units_per_base: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PriceUpdate {
+    pub minor_units: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CurrencyPreference {
+    pub currency: Currency,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ProductSale {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub sale_price: i64,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub starts_at: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub ends_at: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewSale {
+    pub sale_price: i64,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_rate_keeps_the_amount() {
+        assert_eq!(BaseRate::identity(Currency::Inr).convert(12_345).unwrap(), Money::new(12_345, Currency::Inr));
+    }
+
+    #[test]
+    fn conversion_rounds_half_away_from_zero() {
+        // 0.0125 USD per INR paisa
+        let rate = BaseRate { currency: Currency::Usd, scaled: Some(125_000_000) };
+        assert_eq!(rate.convert(100).unwrap().minor_units, 1);
+        assert_eq!(rate.convert(40).unwrap().minor_units, 1);
+        assert_eq!(rate.convert(39).unwrap().minor_units, 0);
+        assert_eq!(rate.convert(-40).unwrap().minor_units, -1);
+    }
+
+    #[test]
+    fn conversion_is_exact_for_large_amounts() {
+        let rate = BaseRate { currency: Currency::Eur, scaled: Some(110_123_456) };
+        assert_eq!(rate.convert(1_000_000_000_000).unwrap().minor_units, 11_012_345_600);
+    }
+
+    #[test]
+    fn missing_rate_is_reported_on_use() {
+        let rate = BaseRate { currency: Currency::Gbp, scaled: None };
+        assert!(matches!(rate.convert(100), Err(PricingError::NoRate { currency: Currency::Gbp })));
+    }
+}
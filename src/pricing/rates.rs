@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::money::{Currency, Money};
+use crate::pricing::models::{BaseRate, PriceQuote, PricingError};
+use crate::product::models::Product;
+
+// One ratio per product: product_prices if set (so regional prices follow sales), else exchange_rates
+pub async fn prices_in(
+    pool: &PgPool,
+    base: Currency,
+    product_ids: &[Uuid],
+    currency: Currency,
+) -> Result<HashMap<Uuid, PriceQuote>, PricingError> {
+    let rows = sqlx::query_as::<_, (Uuid, Option<i64>, Option<i64>, Option<i64>, Option<chrono::DateTime<chrono::Utc>>)>(
+        r#"
+        WITH quotes AS (
+            SELECT p.id,
+                   p.price,
+                   COALESCE(sale.sale_price, p.price) AS effective,
+                   CASE WHEN sale.sale_price IS NOT NULL THEN GREATEST(p.price, COALESCE(p.compare_at_price, 0))
+                        ELSE p.compare_at_price END AS compare_at,
+                   sale.ends_at AS sale_ends_at,
+                   LEAST(
+                       (SELECT MIN(h.price) FROM product_price_history h
+                        WHERE h.product_id = p.id AND h.currency = p.currency
+                          AND h.recorded_at > anchor.at - INTERVAL '30 days'
+                          AND h.recorded_at < anchor.at),
+                       (SELECT h.price FROM product_price_history h
+                        WHERE h.product_id = p.id AND h.currency = p.currency
+                          AND h.recorded_at <= anchor.at - INTERVAL '30 days'
+                        ORDER BY h.recorded_at DESC LIMIT 1),
+                       (SELECT MIN(s.sale_price) FROM product_sales s
+                        WHERE s.product_id = p.id AND s.id IS DISTINCT FROM sale.id
+                          AND s.starts_at < anchor.at
+                          AND s.ends_at > anchor.at - INTERVAL '30 days')
+                   ) AS lowest_30d,
+                   CASE
+                       WHEN p.currency = $2 THEN 1::NUMERIC
+                       WHEN pp.amount IS NOT NULL AND p.price > 0 THEN pp.amount::NUMERIC / p.price
+                       ELSE COALESCE(dst.units_per_base, CASE WHEN $2 = $3 THEN 1 END)
+                            / COALESCE(src.units_per_base, CASE WHEN p.currency = $3 THEN 1 END)
+                   END AS ratio
+            FROM products p
+            LEFT JOIN LATERAL (
+                SELECT s.id, s.sale_price, s.starts_at, s.ends_at FROM product_sales s
+                WHERE s.product_id = p.id AND s.starts_at <= now() AND s.ends_at > now()
+                ORDER BY s.sale_price LIMIT 1
+            ) sale ON TRUE
+            -- The disclosed lowest price covers the 30 days before the running sale began
+            CROSS JOIN LATERAL (SELECT COALESCE(sale.starts_at, now()) AS at) anchor
+            LEFT JOIN product_prices pp ON pp.product_id = p.id AND pp.currency = $2
+            LEFT JOIN exchange_rates src ON src.currency = p.currency
+            LEFT JOIN exchange_rates dst ON dst.currency = $2
+            WHERE p.id = ANY($1)
+        )
+        SELECT id,
+               ROUND(effective * ratio)::BIGINT,
+               ROUND(compare_at * ratio)::BIGINT,
+               ROUND(lowest_30d * ratio)::BIGINT,
+               sale_ends_at
+        FROM quotes
+        "#
+    )
+        .bind(product_ids)
+        .bind(currency.as_str())
+        .bind(base.as_str())
+        .fetch_all(pool)
+        .await?;
+
+    let mut quotes = HashMap::with_capacity(rows.len());
+    for (product_id, price, compare_at, lowest_30d, sale_ends_at) in rows {
+        let price = match price {
+            Some(price) => Money::new(price, currency),
+            None => return Err(PricingError::Unpriceable { product_id, currency }),
+        };
+        quotes.insert(product_id, PriceQuote {
+            price,
+            compare_at_price: compare_at.map(|amount| Money::new(amount, currency)),
+            lowest_price_30d: lowest_30d.map(|amount| Money::new(amount, currency)),
+            sale_ends_at,
+        });
+    }
+    Ok(quotes)
+}
+
+pub async fn base_rate(pool: &PgPool, base: Currency, currency: Currency) -> Result<BaseRate, PricingError> {
+    if currency == base {
+        return Ok(BaseRate::identity(currency));
+    }
+
+    // Too wide for BIGINT once scaled, so it comes over as text
+    let scaled = sqlx::query_scalar::<_, String>(
+        "SELECT (units_per_base * 10000000000)::NUMERIC(30, 0)::TEXT FROM exchange_rates WHERE currency = $1"
+    )
+        .bind(currency.as_str())
+        .fetch_optional(pool)
+        .await?;
+
+    let scaled = match scaled {
+        Some(scaled) => Some(scaled.parse::<i128>().map_err(|e| sqlx::Error::Decode(Box::new(e)))?),
+        None => None,
+    };
+    Ok(BaseRate { currency, scaled })
+}
+
+pub async fn localize_products(
+    pool: &PgPool,
+    base: Currency,
+    products: &mut [Product],
+    currency: Currency,
+) -> Result<(), PricingError> {
+    let ids: Vec<Uuid> = products.iter().map(|p| p.id).collect();
+    let mut quotes = prices_in(pool, base, &ids, currency).await?;
+
+    for product in products.iter_mut() {
+        if let Some(quote) = quotes.remove(&product.id) {
+            product.price = quote.price;
+            product.compare_at_price = quote.compare_at_price;
+            product.lowest_price_30d = quote.lowest_price_30d;
+            product.sale_ends_at = quote.sale_ends_at;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only the columns prices_in reads; the base schema is not part of migrations/
+    const SCHEMA: &str = r#"
+        CREATE TABLE products (id UUID PRIMARY KEY, price BIGINT NOT NULL, currency TEXT NOT NULL, compare_at_price BIGINT);
+        CREATE TABLE product_sales (id UUID PRIMARY KEY DEFAULT gen_random_uuid(), product_id UUID NOT NULL,
+            sale_price BIGINT NOT NULL, starts_at TIMESTAMPTZ NOT NULL, ends_at TIMESTAMPTZ NOT NULL);
+        CREATE TABLE product_price_history (product_id UUID NOT NULL, price BIGINT NOT NULL, currency TEXT NOT NULL,
+            recorded_at TIMESTAMPTZ NOT NULL);
+        CREATE TABLE product_prices (product_id UUID NOT NULL, currency TEXT NOT NULL, amount BIGINT NOT NULL);
+        CREATE TABLE exchange_rates (currency TEXT PRIMARY KEY, units_per_base NUMERIC(20, 10) NOT NULL);
+    "#;
+
+    #[sqlx::test(migrations = false)]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn disclosed_lowest_price_predates_the_running_sale(pool: PgPool) {
+        sqlx::raw_sql(SCHEMA).execute(&pool).await.unwrap();
+        let product_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO products (id, price, currency) VALUES ($1, 1000, 'inr')")
+            .bind(product_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO product_price_history (product_id, price, currency, recorded_at)
+             VALUES ($1, 1000, 'inr', now() - INTERVAL '60 days')"
+        )
+            .bind(product_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO product_sales (product_id, sale_price, starts_at, ends_at)
+             VALUES ($1, 800, now() - INTERVAL '1 day', now() + INTERVAL '7 days')"
+        )
+            .bind(product_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let quote = prices_in(&pool, Currency::Inr, &[product_id], Currency::Inr).await.unwrap()
+            .remove(&product_id)
+            .unwrap();
+        assert_eq!(quote.price, Money::new(800, Currency::Inr));
+        assert_eq!(quote.lowest_price_30d, Some(Money::new(1000, Currency::Inr)));
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use serde_json::Value;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use crate::product::models::{AttributeDefinition, AttributeType, Facet, FacetValue};
+
+pub const FILTER_PREFIX: &str = "attr.";
+
+#[derive(Debug)]
+pub enum AttributeFilter {
+    // attr.brand=Acme,Globex
+    OneOf { key: String, values: Vec<String> },
+    // attr.screen_size=13..16, either bound may be omitted
+    Range { key: String, min: Option<f64>, max: Option<f64> },
+    // attr.waterproof=true
+    Is { key: String, value: bool },
+}
+
+pub async fn definitions(pool: &PgPool) -> Result<HashMap<String, AttributeDefinition>, sqlx::Error> {
+    let definitions = sqlx::query_as::<_, AttributeDefinition>("SELECT * FROM attribute_definitions")
+        .fetch_all(pool)
+        .await?;
+    Ok(definitions.into_iter().map(|d| (d.key.clone(), d)).collect())
+}
+
+pub fn parse_filters(
+    definitions: &HashMap<String, AttributeDefinition>,
+    params: &HashMap<String, String>,
+) -> Result<Vec<AttributeFilter>, String> {
+    let mut filters = vec![];
+
+    for (param, raw) in params {
+        let key = match param.strip_prefix(FILTER_PREFIX) {
+            Some(key) => key.to_string(),
+            None => continue,
+        };
+        let definition = definitions
+            .get(&key)
+            .ok_or_else(|| format!("Unknown attribute: {}", key))?;
+
+        let filter = match definition.data_type {
+            AttributeType::Text => AttributeFilter::OneOf {
+                key,
+                values: raw.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect(),
+            },
+            AttributeType::Number => {
+                let (min, max) = raw.split_once("..").unwrap_or((raw.as_str(), raw.as_str()));
+                let bound = |v: &str| -> Result<Option<f64>, String> {
+                    if v.trim().is_empty() {
+                        Ok(None)
+                    } else {
+                        v.trim().parse::<f64>().map(Some).map_err(|_| format!("{} expects a number or range", key))
+                    }
+                };
+                AttributeFilter::Range { min: bound(min)?, max: bound(max)?, key }
+            }
+            AttributeType::Boolean => AttributeFilter::Is {
+                value: raw.parse::<bool>().map_err(|_| format!("{} expects true or false", key))?,
+                key,
+            },
+        };
+        filters.push(filter);
+    }
+
+    Ok(filters)
+}
+
+impl AttributeFilter {
+    pub fn key(&self) -> &str {
+        match self {
+            AttributeFilter::OneOf { key, .. } | AttributeFilter::Range { key, .. } | AttributeFilter::Is { key, .. } => key,
+        }
+    }
+}
+
+pub fn push_conditions<'f>(
+    qb: &mut QueryBuilder<'_, Postgres>,
+    search: Option<&str>,
+    filters: impl IntoIterator<Item = &'f AttributeFilter>,
+) {
+    qb.push(" WHERE TRUE");
+
+    if let Some(term) = search {
+        let pattern = format!("%{}%", term);
+        qb.push(" AND (LOWER(name) LIKE LOWER(")
+            .push_bind(pattern.clone())
+            .push(") OR LOWER(description) LIKE LOWER(")
+            .push_bind(pattern)
+            .push("))");
+    }
+
+    // Equality filters use @> so the GIN index on attributes applies
+    for filter in filters {
+        match filter {
+            AttributeFilter::OneOf { key, values } => {
+                qb.push(" AND (FALSE");
+                for value in values {
+                    qb.push(" OR attributes @> jsonb_build_object(").push_bind(key.clone())
+                        .push(", ").push_bind(value.clone()).push(")");
+                }
+                qb.push(")");
+            }
+            AttributeFilter::Range { key, min, max } => {
+                // CASE keeps the cast away from values that are not numbers
+                for (bound, op) in [(min, " >= "), (max, " <= ")] {
+                    if let Some(bound) = bound {
+                        qb.push(" AND CASE WHEN jsonb_typeof(attributes->").push_bind(key.clone())
+                            .push(") = 'number' THEN (attributes->>").push_bind(key.clone())
+                            .push(")::float8 END").push(op).push_bind(*bound);
+                    }
+                }
+            }
+            AttributeFilter::Is { key, value } => {
+                qb.push(" AND attributes @> jsonb_build_object(").push_bind(key.clone())
+                    .push(", ").push_bind(*value).push(")");
+            }
+        }
+    }
+}
+
+// An attribute's own filter is left out of its counts, so its other values stay selectable
+pub async fn facets(
+    pool: &PgPool,
+    definitions: &HashMap<String, AttributeDefinition>,
+    search: Option<&str>,
+    filters: &[AttributeFilter],
+) -> Result<Vec<Facet>, sqlx::Error> {
+    const COUNT: &str =
+        "SELECT a.key, a.value, COUNT(*) AS count FROM products CROSS JOIN LATERAL jsonb_each_text(attributes) AS a(key, value)";
+
+    let filtered_keys: Vec<String> = filters.iter().map(|filter| filter.key().to_string()).collect();
+    let mut qb = QueryBuilder::<Postgres>::new("SELECT key, value, count FROM (");
+
+    qb.push(COUNT);
+    push_conditions(&mut qb, search, filters);
+    qb.push(" AND a.key <> ALL(").push_bind(filtered_keys.clone()).push(") GROUP BY a.key, a.value");
+
+    for key in &filtered_keys {
+        qb.push(" UNION ALL ").push(COUNT);
+        push_conditions(&mut qb, search, filters.iter().filter(|filter| filter.key() != key));
+        qb.push(" AND a.key = ").push_bind(key.clone()).push(" GROUP BY a.key, a.value");
+    }
+    qb.push(") f ORDER BY key, count DESC, value");
+
+    let counts = qb.build_query_as::<(String, String, i64)>().fetch_all(pool).await?;
+
+    let mut facets: Vec<Facet> = vec![];
+    for (key, value, count) in counts {
+        let definition = match definitions.get(&key) {
+            Some(definition) if definition.facetable => definition,
+            _ => continue,
+        };
+        match facets.last_mut() {
+            Some(facet) if facet.key == key => facet.values.push(FacetValue { value, count }),
+            _ => facets.push(Facet {
+                key,
+                label: definition.label.clone(),
+                values: vec![FacetValue { value, count }],
+            }),
+        }
+    }
+    Ok(facets)
+}
+
+pub fn validate_attributes(
+    definitions: &HashMap<String, AttributeDefinition>,
+    attributes: &Value,
+) -> Vec<String> {
+    let object = match attributes.as_object() {
+        Some(object) => object,
+        None => return vec!["attributes must be a JSON object".to_string()],
+    };
+
+    let mut errors = vec![];
+    for (key, value) in object {
+        let matches = match definitions.get(key).map(|d| d.data_type) {
+            Some(AttributeType::Text) => value.is_string(),
+            Some(AttributeType::Number) => value.is_number(),
+            Some(AttributeType::Boolean) => value.is_boolean(),
+            None => {
+                errors.push(format!("Unknown attribute: {}", key));
+                continue;
+            }
+        };
+        if !matches {
+            errors.push(format!("{} has the wrong type", key));
+        }
+    }
+    errors
+}
@@ -1,44 +1,141 @@
 use crate::AppState;
 use crate::auth::models::User;
-use crate::product::models::Product;
+use crate::auth::models::{AdminUser, AuthenticatedUser};
+use crate::bundle::components::components_for_products;
+use crate::http_cache::conditional::{listing_validators, product_validators};
+use crate::cache::{listing_key, product_key};
+use crate::http_cache::models::{CachedResponse, CachedRoute};
+use crate::locale::models::RequestLocale;
+use crate::locale::translate::translate_products;
+use crate::money::Currency;
+use crate::pricing::models::{PricingError, RequestCurrency};
+use crate::pricing::rates::localize_products;
+use crate::product::attributes::{definitions, facets, parse_filters, push_conditions, validate_attributes};
+use crate::product::history::{begin_as, history, revert};
+use crate::product::images::{image_keys, image_view, images_for_products, original_key, process_upload, thumbnail_key};
+use crate::product::models::{AttributeDefinition, ImageOrder, NewProduct, Product, ProductUpdate, ProductDetails, ProductImage, ProductListing, RelatedQuery, SuggestQuery};
+use crate::product::suggest::suggest;
+use crate::question::listing::approved_questions;
+use crate::question::models::QuestionSort;
+use crate::product::related::related_products;
+use crate::storage::{content_type_for, StorageError};
 use crate::routes::models::ApiResponse;
-use actix_web::{HttpResponse, Responder, web};
+use actix_multipart::Multipart;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use futures::StreamExt;
 use chrono::Month::April;
-use sqlx::{Error, PgPool};
+use sqlx::{Error, PgPool, Postgres, QueryBuilder};
+use std::collections::HashMap;
 use uuid::Uuid;
 
-pub async fn get_all_products(data: web::Data<AppState>) -> impl Responder {
-    let products = sqlx::query_as::<_, Product>("select * from products")
-        .fetch_all(&data.db_pool)
-        .await;
+pub async fn get_all_products(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<HashMap<String, String>>,
+    currency: RequestCurrency,
+    locale: RequestLocale,
+) -> impl Responder {
+    let cache_control = data.cache_policies.for_route(CachedRoute::ProductList);
+    let uri = req.uri().to_string();
+    let key = listing_key(&uri, currency.0, &locale.0);
+    if let Some(cached) = data.catalogue_cache.get::<CachedResponse>(&key).await {
+        return cached.respond(&req, cache_control);
+    }
 
-    match products {
-        Ok(products) => {
-            return HttpResponse::Ok().json(ApiResponse {
+    let generation = data.catalogue_cache.generation();
+    let validators = match listing_validators(&data.db_pool, &uri, currency.0, &locale.0).await {
+        Ok(validators) => validators,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+    if validators.is_fresh(&req) {
+        return validators.not_modified(cache_control);
+    }
+
+    match filtered_listing(&data, None, &query, currency.0, &locale.0).await {
+        Ok(listing) => {
+            let response = ApiResponse {
                 status: "Success".to_string(),
                 msg: "All products".to_string(),
-                data: products,
-            });
-        }
-        Err(e) => {
-            return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+                data: listing,
+            };
+            match CachedResponse::new(validators, &response) {
+                Ok(cached) => {
+                    data.catalogue_cache.put(&key, generation, &cached).await;
+                    cached.respond(&req, cache_control)
+                }
+                Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+            }
         }
+        Err(ListingError::BadFilter(e)) => HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: e,
+            data: "No Data".to_string(),
+        }),
+        Err(ListingError::Pricing(e)) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
     }
 }
 
-pub async fn get_product_by_id(data: web::Data<AppState>, id: web::Path<Uuid>) -> impl Responder {
+const PRODUCT_DETAIL_QUESTIONS: i64 = 5;
+
+pub async fn get_product_by_id(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    currency: RequestCurrency,
+    locale: RequestLocale,
+    user: Option<AuthenticatedUser>,
+) -> impl Responder {
+    if let Some(user_id) = user.and_then(|user| Uuid::parse_str(&user.claims.sub).ok()) {
+        data.recently_viewed.record(user_id, *id);
+    }
+
+    let cache_control = data.cache_policies.for_route(CachedRoute::ProductDetail);
+    let uri = req.uri().to_string();
+    let key = product_key(*id, &uri, currency.0, &locale.0);
+    if let Some(cached) = data.catalogue_cache.get::<CachedResponse>(&key).await {
+        return cached.respond(&req, cache_control);
+    }
+
+    let generation = data.catalogue_cache.generation();
+    let validators = match product_validators(&data.db_pool, *id, &uri, currency.0, &locale.0).await {
+        Ok(validators) => validators,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+    if let Some(validators) = &validators
+        && validators.is_fresh(&req)
+    {
+        return validators.not_modified(cache_control);
+    }
+
     let product = sqlx::query_as::<_, Product>("select * from products where id = $1")
         .bind(*id)
         .fetch_one(&data.db_pool)
         .await;
 
-    match product {
-        Ok(product) => HttpResponse::Ok().json(ApiResponse {
-            status: "Success".to_string(),
-            msg: "Product Details".to_string(),
-            data: product,
-        }),
-        Err(_) => HttpResponse::Ok().json(ApiResponse {
+    match (product, validators) {
+        (Ok(product), Some(validators)) => match product_details(&data, vec![product], currency.0, &locale.0).await {
+            Ok(mut products) => {
+                let mut details = products.remove(0);
+                match approved_questions(&data.db_pool, details.product.id, QuestionSort::MostUpvoted, PRODUCT_DETAIL_QUESTIONS, 0).await {
+                    Ok(questions) => details.questions = Some(questions),
+                    Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+                }
+                let response = ApiResponse {
+                    status: "Success".to_string(),
+                    msg: "Product Details".to_string(),
+                    data: details,
+                };
+                match CachedResponse::new(validators, &response) {
+                    Ok(cached) => {
+                        data.catalogue_cache.put(&key, generation, &cached).await;
+                        cached.respond(&req, cache_control)
+                    }
+                    Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+                }
+            }
+            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        },
+        _ => HttpResponse::Ok().json(ApiResponse {
             status: "Error".to_string(),
             msg: "Error while fetching data for the Id".to_string(),
             data: "No Data".to_string(),
@@ -46,34 +143,631 @@ pub async fn get_product_by_id(data: web::Data<AppState>, id: web::Path<Uuid>) -
     }
 }
 
-pub async fn search_product(data: web::Data<AppState>, query: web::Query<String>) -> impl Responder {
-    let search_term = &query.into_inner();
+pub async fn suggest_products(data: web::Data<AppState>, query: web::Query<SuggestQuery>) -> impl Responder {
+    let limit = query.limit.unwrap_or(8).clamp(1, 20);
 
-    // Search the products by name or any other attributes, you can modify this query based on your needs
-    let products = sqlx::query_as::<_, Product>(
-        "SELECT * FROM products WHERE LOWER(name) LIKE LOWER($1) OR LOWER(description) LIKE LOWER($1)"
-    )
-        .bind(format!("%{}%", search_term))  // Bind the search term with wildcards for a partial match
-        .fetch_all(&data.db_pool)
-        .await;
+    match suggest(&data.db_pool, &query.q, limit).await {
+        Ok(suggestions) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Suggestions".to_string(),
+            data: suggestions,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn get_related_products(
+    data: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    query: web::Query<RelatedQuery>,
+    currency: RequestCurrency,
+    locale: RequestLocale,
+) -> impl Responder {
+    let limit = query.limit.unwrap_or(8).clamp(1, 50);
 
-    match products {
-        Ok(products) => {
-            if products.is_empty() {
+    let products = match related_products(&data.db_pool, id.into_inner(), limit).await {
+        Ok(products) => products,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    match product_details(&data, products, currency.0, &locale.0).await {
+        Ok(products) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Related products".to_string(),
+            data: products,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn search_product(
+    data: web::Data<AppState>,
+    query: web::Query<HashMap<String, String>>,
+    currency: RequestCurrency,
+    locale: RequestLocale,
+) -> impl Responder {
+    let search_term = query.get("q").map(|q| q.trim()).unwrap_or_default();
+
+    // Search the products by name or description, narrowed by any `attr.*` filters
+    match filtered_listing(&data, Some(search_term), &query, currency.0, &locale.0).await {
+        Ok(listing) => {
+            if listing.products.is_empty() {
                 return HttpResponse::Ok().json(ApiResponse {
                     status: "Error".to_string(),
                     msg: "No products found".to_string(),
                     data: "No Data".to_string(),
                 });
             }
-            return HttpResponse::Ok().json(ApiResponse {
+            HttpResponse::Ok().json(ApiResponse {
                 status: "Success".to_string(),
                 msg: "Search results".to_string(),
-                data: products,
-            });
+                data: listing,
+            })
+        }
+        Err(ListingError::BadFilter(e)) => HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: e,
+            data: "No Data".to_string(),
+        }),
+        Err(ListingError::Pricing(e)) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+enum ListingError {
+    BadFilter(String),
+    Pricing(PricingError),
+}
+
+impl From<sqlx::Error> for ListingError {
+    fn from(e: sqlx::Error) -> Self {
+        ListingError::Pricing(PricingError::Db(e))
+    }
+}
+
+async fn filtered_listing(
+    data: &AppState,
+    search: Option<&str>,
+    params: &HashMap<String, String>,
+    currency: Currency,
+    locale: &str,
+) -> Result<ProductListing, ListingError> {
+    let definitions = definitions(&data.db_pool).await?;
+    let filters = parse_filters(&definitions, params).map_err(ListingError::BadFilter)?;
+
+    let mut qb = QueryBuilder::<Postgres>::new("SELECT * FROM products");
+    push_conditions(&mut qb, search, &filters);
+    qb.push(" ORDER BY created_at DESC");
+    let products = qb.build_query_as::<Product>().fetch_all(&data.db_pool).await?;
+
+    let facets = facets(&data.db_pool, &definitions, search, &filters).await?;
+    let products = product_details(data, products, currency, locale).await.map_err(ListingError::Pricing)?;
+
+    Ok(ProductListing { products, facets })
+}
+
+pub async fn product_details(
+    data: &AppState,
+    mut products: Vec<Product>,
+    currency: Currency,
+    locale: &str,
+) -> Result<Vec<ProductDetails>, PricingError> {
+    localize_products(&data.db_pool, data.base_currency, &mut products, currency).await?;
+    translate_products(&data.db_pool, &mut products, locale).await?;
+
+    let ids: Vec<Uuid> = products.iter().map(|p| p.id).collect();
+    let mut images = images_for_products(&data.db_pool, data.blob_store.as_ref(), &ids).await?;
+    let mut components = components_for_products(&data.db_pool, &ids).await?;
+
+    Ok(products
+        .into_iter()
+        .map(|product| ProductDetails {
+            images: images.remove(&product.id).unwrap_or_default(),
+            components: components.remove(&product.id).unwrap_or_default(),
+            questions: None,
+            product,
+        })
+        .collect())
+}
+
+fn max_image_bytes() -> usize {
+    std::env::var("MAX_IMAGE_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(10 * 1024 * 1024)
+}
+
+pub async fn upload_product_images(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    id: web::Path<Uuid>,
+    mut payload: Multipart,
+) -> impl Responder {
+    let product_id = id.into_inner();
+
+    let next_position = sqlx::query_scalar::<_, i32>(
+        "SELECT COALESCE((SELECT MAX(position) + 1 FROM product_images WHERE product_id = p.id), 0) FROM products p WHERE p.id = $1"
+    )
+        .bind(product_id)
+        .fetch_optional(&data.db_pool)
+        .await;
+
+    let mut position = match next_position {
+        Ok(Some(position)) => position,
+        Ok(None) => return HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let limit = max_image_bytes();
+    let mut uploaded = vec![];
+
+    while let Some(field) = payload.next().await {
+        let mut field = match field {
+            Ok(field) => field,
+            Err(e) => return HttpResponse::BadRequest().body(format!("Invalid multipart body: {}", e)),
+        };
+
+        if field.name() != Some("image") {
+            continue;
         }
-        Err(e) => {
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.next().await {
+            match chunk {
+                Ok(chunk) if bytes.len() + chunk.len() <= limit => bytes.extend_from_slice(&chunk),
+                Ok(_) => return HttpResponse::PayloadTooLarge().body(format!("Images are limited to {} bytes", limit)),
+                Err(e) => return HttpResponse::BadRequest().body(format!("Invalid multipart body: {}", e)),
+            }
+        }
+
+        let processed = match web::block(move || process_upload(bytes)).await {
+            Ok(Ok(processed)) => processed,
+            Ok(Err(e)) => return HttpResponse::BadRequest().body(format!("Invalid image: {}", e)),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        };
+
+        let image_id = Uuid::new_v4();
+        let store = data.blob_store.as_ref();
+
+        let original = original_key(product_id, image_id, processed.extension);
+        if let Err(e) = store.put(&original, processed.original, content_type_for(&original)).await {
             return HttpResponse::InternalServerError().body(format!("Error: {}", e));
         }
+        for (size, thumbnail) in processed.thumbnails {
+            let key = thumbnail_key(product_id, image_id, size);
+            if let Err(e) = store.put(&key, thumbnail, "image/jpeg").await {
+                return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+            }
+        }
+
+        let image = sqlx::query_as::<_, ProductImage>(
+            "INSERT INTO product_images (id, product_id, position, extension, width, height) \
+             VALUES ($1, $2, $3, $4, $5, $6) RETURNING *"
+        )
+            .bind(image_id)
+            .bind(product_id)
+            .bind(position)
+            .bind(processed.extension)
+            .bind(processed.width as i32)
+            .bind(processed.height as i32)
+            .fetch_one(&data.db_pool)
+            .await;
+
+        match image {
+            Ok(image) => uploaded.push(image_view(store, &image)),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        }
+        position += 1;
     }
-}
\ No newline at end of file
+
+    if uploaded.is_empty() {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "No `image` fields in the upload".to_string(),
+            data: "No Data".to_string(),
+        });
+    }
+
+    HttpResponse::Ok().json(ApiResponse {
+        status: "Success".to_string(),
+        msg: "Images uploaded".to_string(),
+        data: uploaded,
+    })
+}
+
+pub async fn delete_product_image(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    path: web::Path<(Uuid, Uuid)>,
+) -> impl Responder {
+    let (product_id, image_id) = path.into_inner();
+
+    let image = sqlx::query_as::<_, ProductImage>(
+        "DELETE FROM product_images WHERE id = $1 AND product_id = $2 RETURNING *"
+    )
+        .bind(image_id)
+        .bind(product_id)
+        .fetch_optional(&data.db_pool)
+        .await;
+
+    match image {
+        Ok(Some(image)) => {
+            for key in image_keys(&image) {
+                if let Err(e) = data.blob_store.delete(&key).await {
+                    log::warn!("Failed to delete blob {}: {}", key, e);
+                }
+            }
+            HttpResponse::Ok().json(ApiResponse {
+                status: "Success".to_string(),
+                msg: "Image deleted".to_string(),
+                data: "{}".to_string(),
+            })
+        }
+        Ok(None) => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Image not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn reorder_product_images(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    id: web::Path<Uuid>,
+    payload: web::Json<ImageOrder>,
+) -> impl Responder {
+    let product_id = id.into_inner();
+
+    let mut tx = match data.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    // Position is the index of the image in the submitted list.
+    let updated = sqlx::query(
+        "UPDATE product_images i SET position = o.position - 1 \
+         FROM UNNEST($2::uuid[]) WITH ORDINALITY AS o(id, position) \
+         WHERE i.id = o.id AND i.product_id = $1"
+    )
+        .bind(product_id)
+        .bind(&payload.image_ids)
+        .execute(&mut *tx)
+        .await;
+
+    match updated {
+        Ok(result) if result.rows_affected() as usize == payload.image_ids.len() => match tx.commit().await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse {
+                status: "Success".to_string(),
+                msg: "Images reordered".to_string(),
+                data: "{}".to_string(),
+            }),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        },
+        // Dropping the transaction rolls back the partial update.
+        Ok(_) => HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Some images do not belong to this product".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn serve_media(data: web::Data<AppState>, key: web::Path<String>) -> impl Responder {
+    // Digital product files share the store but only go out through signed /downloads links
+    if !key.starts_with("products/") {
+        return HttpResponse::NotFound().finish();
+    }
+    match data.blob_store.get(&key).await {
+        Ok(bytes) => HttpResponse::Ok().content_type(content_type_for(&key)).body(bytes),
+        Err(StorageError::NotFound) => HttpResponse::NotFound().finish(),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn get_attribute_definitions(data: web::Data<AppState>) -> impl Responder {
+    let definitions = sqlx::query_as::<_, AttributeDefinition>("SELECT * FROM attribute_definitions ORDER BY key")
+        .fetch_all(&data.db_pool)
+        .await;
+
+    match definitions {
+        Ok(definitions) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Attribute definitions".to_string(),
+            data: definitions,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn define_attribute(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    key: web::Path<String>,
+    payload: web::Json<AttributeDefinition>,
+) -> impl Responder {
+    let mut tx = match data.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    // Products whose stored value would not fit the new type
+    let mismatched = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM products WHERE attributes ? $1 AND jsonb_typeof(attributes->$1) <> $2"
+    )
+        .bind(key.as_str())
+        .bind(payload.data_type.json_type())
+        .fetch_one(&mut *tx)
+        .await;
+
+    match mismatched {
+        Ok(0) => {}
+        Ok(count) => return HttpResponse::Conflict().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: format!("{} products have {} values that are not {}; update them first", count, key, payload.data_type.as_str()),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+
+    let result = sqlx::query(
+        "INSERT INTO attribute_definitions (key, label, data_type, facetable) VALUES ($1, $2, $3, $4) \
+         ON CONFLICT (key) DO UPDATE SET label = EXCLUDED.label, data_type = EXCLUDED.data_type, facetable = EXCLUDED.facetable"
+    )
+        .bind(key.as_str())
+        .bind(&payload.label)
+        .bind(payload.data_type.as_str())
+        .bind(payload.facetable)
+        .execute(&mut *tx)
+        .await;
+
+    match result {
+        Ok(_) => match tx.commit().await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse {
+                status: "Success".to_string(),
+                msg: "Attribute saved".to_string(),
+                data: "{}".to_string(),
+            }),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        },
+        Err(sqlx::Error::Database(e)) if e.is_check_violation() => HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Attribute keys may only contain a-z, 0-9 and _".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn set_product_attributes(
+    data: web::Data<AppState>,
+    admin: AdminUser,
+    id: web::Path<Uuid>,
+    payload: web::Json<serde_json::Value>,
+) -> impl Responder {
+    let definitions = match definitions(&data.db_pool).await {
+        Ok(definitions) => definitions,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let errors = validate_attributes(&definitions, &payload);
+    if !errors.is_empty() {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Invalid attributes".to_string(),
+            data: errors,
+        });
+    }
+
+    let mut tx = match begin_as(&data.db_pool, Uuid::parse_str(&admin.claims.sub).ok()).await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let result = sqlx::query("UPDATE products SET attributes = $1 WHERE id = $2")
+        .bind(payload.into_inner())
+        .bind(id.into_inner())
+        .execute(&mut *tx)
+        .await;
+
+    match result {
+        Ok(result) if result.rows_affected() > 0 => match tx.commit().await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse {
+                status: "Success".to_string(),
+                msg: "Attributes updated".to_string(),
+                data: "{}".to_string(),
+            }),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        },
+        Ok(_) => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn create_product(
+    data: web::Data<AppState>,
+    admin: AdminUser,
+    payload: web::Json<NewProduct>,
+) -> impl Responder {
+    if payload.name.trim().is_empty() || payload.price.minor_units < 0
+        || payload.stock_quantity.is_some_and(|stock| stock < 0)
+    {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Name is required and price and stock cannot be negative".to_string(),
+            data: "No Data".to_string(),
+        });
+    }
+
+    let mut tx = match begin_as(&data.db_pool, Uuid::parse_str(&admin.claims.sub).ok()).await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let product = sqlx::query_as::<_, Product>(
+        "INSERT INTO products (name, description, price, currency, available, stock_quantity, sku, category_id) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *"
+    )
+        .bind(payload.name.trim())
+        .bind(&payload.description)
+        .bind(payload.price.minor_units)
+        .bind(payload.price.currency.as_str())
+        .bind(payload.available)
+        .bind(payload.stock_quantity)
+        .bind(&payload.sku)
+        .bind(payload.category_id)
+        .fetch_one(&mut *tx)
+        .await;
+
+    match product {
+        Ok(product) => match tx.commit().await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse {
+                status: "Success".to_string(),
+                msg: "Product created".to_string(),
+                data: product,
+            }),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        },
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() || e.is_foreign_key_violation() => {
+            HttpResponse::BadRequest().json(ApiResponse {
+                status: "Error".to_string(),
+                msg: "SKU already in use or category not found".to_string(),
+                data: "No Data".to_string(),
+            })
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn update_product(
+    data: web::Data<AppState>,
+    admin: AdminUser,
+    id: web::Path<Uuid>,
+    payload: web::Json<ProductUpdate>,
+) -> impl Responder {
+    if payload.price.is_some_and(|price| price.minor_units < 0)
+        || payload.compare_at_price.is_some_and(|price| price < 0)
+        || payload.stock_quantity.is_some_and(|stock| stock < 0)
+    {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Prices and stock cannot be negative".to_string(),
+            data: "No Data".to_string(),
+        });
+    }
+
+    let mut tx = match begin_as(&data.db_pool, Uuid::parse_str(&admin.claims.sub).ok()).await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let product = sqlx::query_as::<_, Product>(
+        "UPDATE products SET \
+            name = COALESCE($2, name), \
+            description = COALESCE($3, description), \
+            price = COALESCE($4, price), \
+            currency = COALESCE($5, currency), \
+            available = COALESCE($6, available), \
+            stock_quantity = COALESCE($7, stock_quantity), \
+            sku = COALESCE($8, sku), \
+            compare_at_price = COALESCE($9, compare_at_price) \
+         WHERE id = $1 RETURNING *"
+    )
+        .bind(id.into_inner())
+        .bind(&payload.name)
+        .bind(&payload.description)
+        .bind(payload.price.map(|price| price.minor_units))
+        .bind(payload.price.map(|price| price.currency.as_str()))
+        .bind(payload.available)
+        .bind(payload.stock_quantity)
+        .bind(&payload.sku)
+        .bind(payload.compare_at_price)
+        .fetch_optional(&mut *tx)
+        .await;
+
+    match product {
+        Ok(Some(product)) => match tx.commit().await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse {
+                status: "Success".to_string(),
+                msg: "Product updated".to_string(),
+                data: product,
+            }),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        },
+        Ok(None) => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        // Also raised when stock would drop below what checkouts have reserved
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() || e.is_check_violation() => {
+            HttpResponse::BadRequest().json(ApiResponse {
+                status: "Error".to_string(),
+                msg: e.message().to_string(),
+                data: "No Data".to_string(),
+            })
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn get_product_history(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    id: web::Path<Uuid>,
+) -> impl Responder {
+    match history(&data.db_pool, id.into_inner()).await {
+        Ok(entries) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Product history".to_string(),
+            data: entries,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn revert_product(
+    data: web::Data<AppState>,
+    admin: AdminUser,
+    path: web::Path<(Uuid, i32)>,
+) -> impl Responder {
+    let (product_id, version) = path.into_inner();
+
+    let mut tx = match begin_as(&data.db_pool, Uuid::parse_str(&admin.claims.sub).ok()).await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    match revert(&mut tx, product_id, version).await {
+        Ok(Some(product)) => match tx.commit().await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse {
+                status: "Success".to_string(),
+                msg: format!("Product reverted to version {}", version),
+                data: product,
+            }),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        },
+        Ok(None) => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product or version not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() || e.is_foreign_key_violation() => {
+            HttpResponse::Conflict().json(ApiResponse {
+                status: "Error".to_string(),
+                msg: e.message().to_string(),
+                data: "No Data".to_string(),
+            })
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
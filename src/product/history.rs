@@ -0,0 +1,60 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+use crate::product::models::{Product, ProductHistoryEntry};
+
+// Product changes in this transaction are attributed to actor_id in product_history
+pub async fn begin_as(pool: &PgPool, actor_id: Option<Uuid>) -> Result<Transaction<'static, Postgres>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    if let Some(actor_id) = actor_id {
+        sqlx::query("SELECT set_config('app.actor_id', $1, true)")
+            .bind(actor_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+    }
+    Ok(tx)
+}
+
+pub async fn history(pool: &PgPool, product_id: Uuid) -> Result<Vec<ProductHistoryEntry>, sqlx::Error> {
+    sqlx::query_as::<_, ProductHistoryEntry>(
+        "SELECT h.id, h.product_id, h.version, h.action, h.actor_id, u.username AS actor, \
+                h.old_values, h.new_values, h.changed_at \
+         FROM product_history h LEFT JOIN users u ON u.id = h.actor_id \
+         WHERE h.product_id = $1 ORDER BY h.id DESC"
+    )
+        .bind(product_id)
+        .fetch_all(pool)
+        .await
+}
+
+// Inventory and review aggregates are live data and are not reverted
+pub async fn revert(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: Uuid,
+    version: i32,
+) -> Result<Option<Product>, sqlx::Error> {
+    sqlx::query_as::<_, Product>(
+        r#"
+        UPDATE products p SET
+            name = CASE WHEN ph.new_values ? 'name' THEN h.name ELSE p.name END,
+            description = CASE WHEN ph.new_values ? 'description' THEN h.description ELSE p.description END,
+            price = CASE WHEN ph.new_values ? 'price' THEN h.price ELSE p.price END,
+            currency = CASE WHEN ph.new_values ? 'currency' THEN h.currency ELSE p.currency END,
+            compare_at_price = CASE WHEN ph.new_values ? 'compare_at_price' THEN h.compare_at_price ELSE p.compare_at_price END,
+            available = CASE WHEN ph.new_values ? 'available' THEN h.available ELSE p.available END,
+            sku = CASE WHEN ph.new_values ? 'sku' THEN h.sku ELSE p.sku END,
+            attributes = CASE WHEN ph.new_values ? 'attributes' THEN h.attributes ELSE p.attributes END,
+            category_id = CASE WHEN ph.new_values ? 'category_id' THEN h.category_id ELSE p.category_id END
+        FROM product_history ph
+        CROSS JOIN LATERAL jsonb_populate_record(NULL::products, ph.new_values) h
+        WHERE p.id = $1
+          AND ph.product_id = $1
+          AND ph.version = $2
+          AND ph.new_values IS NOT NULL
+        RETURNING p.*
+        "#
+    )
+        .bind(product_id)
+        .bind(version)
+        .fetch_optional(&mut **tx)
+        .await
+}
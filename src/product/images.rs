@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use image::codecs::jpeg::JpegEncoder;
+use image::ImageFormat;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::product::models::{ProductImage, ProductImageView};
+use crate::storage::BlobStore;
+
+pub const THUMBNAIL_SIZES: [(&str, u32); 3] = [("small", 150), ("medium", 400), ("large", 800)];
+
+pub struct ProcessedImage {
+    pub original: Vec<u8>,
+    pub extension: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub thumbnails: Vec<(&'static str, Vec<u8>)>,
+}
+
+// CPU heavy, so run it on a blocking thread
+pub fn process_upload(bytes: Vec<u8>) -> Result<ProcessedImage, String> {
+    let format = image::guess_format(&bytes).map_err(|e| e.to_string())?;
+    let extension = match format {
+        ImageFormat::Png => "png",
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::WebP => "webp",
+        _ => return Err("Only PNG, JPEG and WebP images are supported".to_string()),
+    };
+
+    let img = image::load_from_memory_with_format(&bytes, format).map_err(|e| e.to_string())?;
+
+    let mut thumbnails = Vec::with_capacity(THUMBNAIL_SIZES.len());
+    for (name, size) in THUMBNAIL_SIZES {
+        let thumbnail = img.thumbnail(size, size).to_rgb8();
+        let mut buf = Cursor::new(Vec::new());
+        JpegEncoder::new_with_quality(&mut buf, 85)
+            .encode_image(&thumbnail)
+            .map_err(|e| e.to_string())?;
+        thumbnails.push((name, buf.into_inner()));
+    }
+
+    Ok(ProcessedImage {
+        original: bytes,
+        extension,
+        width: img.width(),
+        height: img.height(),
+        thumbnails,
+    })
+}
+
+pub fn original_key(product_id: Uuid, image_id: Uuid, extension: &str) -> String {
+    format!("products/{}/{}/original.{}", product_id, image_id, extension)
+}
+
+pub fn thumbnail_key(product_id: Uuid, image_id: Uuid, size: &str) -> String {
+    format!("products/{}/{}/{}.jpg", product_id, image_id, size)
+}
+
+pub fn image_keys(image: &ProductImage) -> Vec<String> {
+    let mut keys = vec![original_key(image.product_id, image.id, &image.extension)];
+    for (size, _) in THUMBNAIL_SIZES {
+        keys.push(thumbnail_key(image.product_id, image.id, size));
+    }
+    keys
+}
+
+pub fn image_view(store: &dyn BlobStore, image: &ProductImage) -> ProductImageView {
+    ProductImageView {
+        id: image.id,
+        position: image.position,
+        width: image.width,
+        height: image.height,
+        url: store.url(&original_key(image.product_id, image.id, &image.extension)),
+        thumbnails: THUMBNAIL_SIZES
+            .iter()
+            .map(|(size, _)| (size.to_string(), store.url(&thumbnail_key(image.product_id, image.id, size))))
+            .collect(),
+    }
+}
+
+pub async fn images_for_products(
+    pool: &PgPool,
+    store: &dyn BlobStore,
+    product_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<ProductImageView>>, sqlx::Error> {
+    let images = sqlx::query_as::<_, ProductImage>(
+        "SELECT * FROM product_images WHERE product_id = ANY($1) ORDER BY product_id, position, created_at"
+    )
+        .bind(product_ids)
+        .fetch_all(pool)
+        .await?;
+
+    let mut by_product: HashMap<Uuid, Vec<ProductImageView>> = HashMap::new();
+    for image in &images {
+        by_product.entry(image.product_id).or_default().push(image_view(store, image));
+    }
+    Ok(by_product)
+}
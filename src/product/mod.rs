@@ -1,2 +1,7 @@
+pub mod attributes;
 pub mod handlers;
+pub mod history;
+pub mod images;
 pub mod models;
+pub mod related;
+pub mod suggest;
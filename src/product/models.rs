@@ -1,14 +1,248 @@
+use std::collections::BTreeMap;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, Row};
 use uuid::Uuid;
+use crate::bundle::models::BundleComponent;
+use crate::money::Money;
+use crate::question::models::Question;
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Product {
     pub id: Uuid,
     pub name: String,
     pub description: Option<String>,
-    pub price: f64,
+    pub price: Money,
+    pub compare_at_price: Option<Money>,
+    pub lowest_price_30d: Option<Money>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub sale_ends_at: Option<DateTime<Utc>>,
     pub available: bool,
+    // None when stock is not tracked
+    pub stock_quantity: Option<i32>,
+    // Bundles derive stock_quantity from their components
+    pub is_bundle: bool,
+    pub is_digital: bool,
+    pub rating_average: f64,
+    pub rating_count: i32,
+    pub attributes: serde_json::Value,
+    pub category_id: Option<Uuid>,
+    pub version: i32,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> FromRow<'r, PgRow> for Product {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        let price = Money::from_columns(row, "price", "currency")?;
+        Ok(Product {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            description: row.try_get("description")?,
+            price,
+            compare_at_price: row
+                .try_get::<Option<i64>, _>("compare_at_price")?
+                .map(|amount| Money::new(amount, price.currency)),
+            // Filled in by pricing::rates::localize_products
+            lowest_price_30d: None,
+            sale_ends_at: None,
+            available: row.try_get("available")?,
+            stock_quantity: row.try_get("stock_quantity")?,
+            is_bundle: row.try_get("is_bundle")?,
+            is_digital: row.try_get("is_digital")?,
+            rating_average: row.try_get("rating_average")?,
+            rating_count: row.try_get("rating_count")?,
+            attributes: row.try_get("attributes")?,
+            category_id: row.try_get("category_id")?,
+            version: row.try_get("version")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ProductImage {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub position: i32,
+    pub extension: String,
+    pub width: i32,
+    pub height: i32,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub created_at: DateTime<Utc>,
 }
+
+#[derive(Debug, Serialize)]
+pub struct ProductImageView {
+    pub id: Uuid,
+    pub position: i32,
+    pub width: i32,
+    pub height: i32,
+    pub url: String,
+    pub thumbnails: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProductDetails {
+    #[serde(flatten)]
+    pub product: Product,
+    pub images: Vec<ProductImageView>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<BundleComponent>,
+    // On the product detail endpoint only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub questions: Option<Vec<Question>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImageOrder {
+    pub image_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttributeType {
+    Text,
+    Number,
+    Boolean,
+}
+
+impl AttributeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttributeType::Text => "text",
+            AttributeType::Number => "number",
+            AttributeType::Boolean => "boolean",
+        }
+    }
+
+    pub fn json_type(&self) -> &'static str {
+        match self {
+            AttributeType::Text => "string",
+            AttributeType::Number => "number",
+            AttributeType::Boolean => "boolean",
+        }
+    }
+}
+
+impl TryFrom<String> for AttributeType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "text" => Ok(AttributeType::Text),
+            "number" => Ok(AttributeType::Number),
+            "boolean" => Ok(AttributeType::Boolean),
+            _ => Err(format!("Unknown attribute type: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AttributeDefinition {
+    #[serde(default)]
+    pub key: String,
+    pub label: String,
+    #[sqlx(try_from = "String")]
+    pub data_type: AttributeType,
+    #[serde(default = "default_facetable")]
+    pub facetable: bool,
+}
+
+fn default_facetable() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize)]
+pub struct FacetValue {
+    pub value: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Facet {
+    pub key: String,
+    pub label: String,
+    pub values: Vec<FacetValue>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProductListing {
+    pub products: Vec<ProductDetails>,
+    pub facets: Vec<Facet>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RelatedQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ProductSuggestion {
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct CategorySuggestion {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Suggestions {
+    pub products: Vec<ProductSuggestion>,
+    pub categories: Vec<CategorySuggestion>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SuggestQuery {
+    pub q: String,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ProductHistoryEntry {
+    pub id: i64,
+    pub product_id: Uuid,
+    pub version: i32,
+    pub action: String,
+    pub actor_id: Option<Uuid>,
+    pub actor: Option<String>,
+    pub old_values: Option<serde_json::Value>,
+    pub new_values: Option<serde_json::Value>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewProduct {
+    pub name: String,
+    pub description: Option<String>,
+    pub price: Money,
+    #[serde(default = "default_available")]
+    pub available: bool,
+    pub stock_quantity: Option<i32>,
+    pub sku: Option<String>,
+    pub category_id: Option<Uuid>,
+}
+
+fn default_available() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductUpdate {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub price: Option<Money>,
+    pub available: Option<bool>,
+    pub stock_quantity: Option<i32>,
+    pub sku: Option<String>,
+    pub compare_at_price: Option<i64>,
+}
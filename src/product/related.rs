@@ -0,0 +1,68 @@
+use std::env;
+use std::time::Duration;
+use log::{error, info};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::product::models::Product;
+
+// Bought together in paid orders, topped up from the same category
+pub async fn related_products(pool: &PgPool, product_id: Uuid, limit: i64) -> Result<Vec<Product>, sqlx::Error> {
+    sqlx::query_as::<_, Product>(
+        r#"
+        WITH co_purchased AS (
+            SELECT p.*, cp.order_count AS score, 0 AS tier
+            FROM product_co_purchases cp
+            JOIN products p ON p.id = cp.related_product_id
+            WHERE cp.product_id = $1 AND p.available
+        ),
+        same_category AS (
+            SELECT p.*, 0::BIGINT AS score, 1 AS tier
+            FROM products p
+            JOIN products source ON source.id = $1
+            WHERE p.category_id = source.category_id
+              AND p.id <> $1
+              AND p.available
+              AND p.id NOT IN (SELECT id FROM co_purchased)
+        )
+        SELECT * FROM (
+            SELECT * FROM co_purchased
+            UNION ALL
+            SELECT * FROM same_category
+        ) ranked
+        ORDER BY tier, score DESC, rating_average DESC, created_at DESC
+        LIMIT $2
+        "#
+    )
+        .bind(product_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn refresh_sales_views(pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query("REFRESH MATERIALIZED VIEW CONCURRENTLY product_co_purchases")
+        .execute(pool)
+        .await?;
+    sqlx::query("REFRESH MATERIALIZED VIEW CONCURRENTLY product_popularity")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub fn spawn_refresh_job(pool: PgPool) {
+    let period = env::var("RECOMMENDATIONS_REFRESH_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(3600);
+
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(Duration::from_secs(period));
+        loop {
+            interval.tick().await;
+            match refresh_sales_views(&pool).await {
+                Ok(()) => info!("Refreshed product sales statistics"),
+                Err(e) => error!("Failed to refresh product sales statistics: {}", e),
+            }
+        }
+    });
+}
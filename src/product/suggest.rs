@@ -0,0 +1,61 @@
+use sqlx::PgPool;
+use crate::product::models::{CategorySuggestion, ProductSuggestion, Suggestions};
+
+fn like_escape(term: &str) -> String {
+    term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+// Prefix matches rank first, trigram similarity catches typos; both use the gin_trgm_ops indexes
+pub async fn suggest(pool: &PgPool, term: &str, limit: i64) -> Result<Suggestions, sqlx::Error> {
+    let term = term.trim().to_lowercase();
+    if term.is_empty() {
+        return Ok(Suggestions { products: vec![], categories: vec![] });
+    }
+    let prefix = format!("{}%", like_escape(&term));
+    let word_prefix = format!("% {}%", like_escape(&term));
+
+    let products = sqlx::query_as::<_, ProductSuggestion>(
+        r#"
+        SELECT p.id, p.name
+        FROM products p
+        LEFT JOIN product_popularity pop ON pop.product_id = p.id
+        WHERE p.available
+          AND (LOWER(p.name) LIKE $2 OR LOWER(p.name) LIKE $3 OR LOWER(p.name) % $1)
+        ORDER BY (LOWER(p.name) LIKE $2) DESC,
+                 (LOWER(p.name) LIKE $3) DESC,
+                 COALESCE(pop.units_sold, 0) DESC,
+                 similarity(LOWER(p.name), $1) DESC,
+                 p.name
+        LIMIT $4
+        "#
+    )
+        .bind(&term)
+        .bind(&prefix)
+        .bind(&word_prefix)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    let categories = sqlx::query_as::<_, CategorySuggestion>(
+        r#"
+        SELECT c.id, c.slug, c.name
+        FROM categories c
+        LEFT JOIN products p ON p.category_id = c.id
+        LEFT JOIN product_popularity pop ON pop.product_id = p.id
+        WHERE LOWER(c.name) LIKE $2 OR LOWER(c.name) LIKE $3 OR LOWER(c.name) % $1
+        GROUP BY c.id
+        ORDER BY (LOWER(c.name) LIKE $2) DESC,
+                 COALESCE(SUM(pop.units_sold), 0) DESC,
+                 c.name
+        LIMIT $4
+        "#
+    )
+        .bind(&term)
+        .bind(&prefix)
+        .bind(&word_prefix)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(Suggestions { products, categories })
+}
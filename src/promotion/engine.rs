@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::cart::models::{Discount, PricedLine};
+use crate::money::{Currency, Money};
+use crate::pricing::models::{BaseRate, PricingError};
+use crate::promotion::models::{Promotion, PromotionOutcome, QuantityTier};
+
+pub async fn active_promotions(pool: &PgPool) -> Result<Vec<Promotion>, sqlx::Error> {
+    let mut promotions = sqlx::query_as::<_, Promotion>(
+        "SELECT * FROM promotions \
+         WHERE active AND (starts_at IS NULL OR starts_at <= $1) AND (ends_at IS NULL OR ends_at > $1) \
+         ORDER BY priority DESC, created_at"
+    )
+        .bind(Utc::now())
+        .fetch_all(pool)
+        .await?;
+    attach_tiers(pool, &mut promotions).await?;
+    Ok(promotions)
+}
+
+pub async fn attach_tiers(pool: &PgPool, promotions: &mut [Promotion]) -> Result<(), sqlx::Error> {
+    let ids: Vec<Uuid> = promotions.iter().map(|promotion| promotion.id).collect();
+    let tiers = sqlx::query_as::<_, (Uuid, i32, i32)>(
+        "SELECT promotion_id, min_quantity, percent_off FROM promotion_tiers \
+         WHERE promotion_id = ANY($1) ORDER BY min_quantity"
+    )
+        .bind(&ids)
+        .fetch_all(pool)
+        .await?;
+
+    let mut by_promotion: HashMap<Uuid, Vec<QuantityTier>> = HashMap::new();
+    for (promotion_id, min_quantity, percent_off) in tiers {
+        by_promotion.entry(promotion_id).or_default().push(QuantityTier { min_quantity, percent_off });
+    }
+    for promotion in promotions.iter_mut() {
+        promotion.tiers = by_promotion.remove(&promotion.id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+fn percent_of(amount: Money, percent: i32) -> Result<Money, PricingError> {
+    amount.share(percent as i64, 100)
+}
+
+fn plural(count: i64) -> &'static str {
+    if count == 1 { "item" } else { "items" }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reward {
+    Reduce(Money),
+    FreeShipping,
+}
+
+// free_shipping names the promotion that waives shipping, if any
+#[derive(Debug, Default)]
+pub struct PromotionRun {
+    pub discounts: Vec<Discount>,
+    pub free_shipping: Option<String>,
+    pub outcomes: Vec<PromotionOutcome>,
+}
+
+fn qualify(
+    rate: &BaseRate,
+    promotion: &Promotion,
+    lines: &[PricedLine],
+    currency: Currency,
+) -> Result<Option<(Reward, String)>, PricingError> {
+    let restricted = !promotion.product_ids.is_empty() || !promotion.category_ids.is_empty();
+    let eligible: Vec<&PricedLine> = lines
+        .iter()
+        .filter(|line| {
+            let product = &line.product.product;
+            !restricted
+                || promotion.product_ids.contains(&product.id)
+                || product.category_id.is_some_and(|id| promotion.category_ids.contains(&id))
+        })
+        .collect();
+    let spend = eligible.iter().try_fold(Money::zero(currency), |sum, line| sum.checked_add(line.line_total))?;
+    let quantity: i64 = eligible.iter().map(|line| line.quantity as i64).sum();
+    if quantity == 0 {
+        return Ok(None);
+    }
+
+    if let (Some(buy), Some(get)) = (promotion.buy_quantity, promotion.get_quantity) {
+        let sets = quantity / (buy + get) as i64;
+        if sets == 0 {
+            return Ok(None);
+        }
+        // The cheapest units are the ones given away
+        let mut by_price = eligible.clone();
+        by_price.sort_by_key(|line| line.unit_price.minor_units);
+        let discounted = sets * get as i64;
+        let mut remaining = discounted;
+        let mut value = Money::zero(currency);
+        for line in by_price {
+            let taken = remaining.min(line.quantity as i64);
+            value = value.checked_add(line.unit_price.times(taken)?)?;
+            remaining -= taken;
+            if remaining == 0 {
+                break;
+            }
+        }
+        let explanation = if promotion.get_percent_off == 100 {
+            format!("Buy {} get {} free: {} {} free", buy, get, discounted, plural(discounted))
+        } else {
+            format!(
+                "Buy {} get {} at {}% off: {} {} discounted",
+                buy, get, promotion.get_percent_off, discounted, plural(discounted)
+            )
+        };
+        return Ok(Some((Reward::Reduce(percent_of(value, promotion.get_percent_off)?), explanation)));
+    }
+
+    if let Some(threshold) = promotion.threshold_amount {
+        let threshold = rate.convert(threshold)?;
+        if spend.minor_units < threshold.minor_units {
+            return Ok(None);
+        }
+        return Ok(Some(match (promotion.percent_off, promotion.amount_off) {
+            (Some(percent), _) => (
+                Reward::Reduce(percent_of(spend, percent)?),
+                format!("Spend {} or more: {}% off", threshold, percent),
+            ),
+            (None, Some(amount)) => {
+                let amount = rate.convert(amount)?;
+                (Reward::Reduce(amount), format!("Spend {} or more: {} off", threshold, amount))
+            }
+            (None, None) => (Reward::FreeShipping, format!("Spend {} or more: free shipping", threshold)),
+        }));
+    }
+
+    let Some(tier) = promotion.tiers.iter().rev().find(|tier| quantity >= tier.min_quantity as i64) else {
+        return Ok(None);
+    };
+    Ok(Some((
+        Reward::Reduce(percent_of(spend, tier.percent_off)?),
+        format!("{} {} reach the {}+ tier: {}% off", quantity, plural(quantity), tier.min_quantity, tier.percent_off),
+    )))
+}
+
+// Qualifying promotions left out by the stacking rules are reported with the reason
+pub fn apply_promotions(
+    rate: &BaseRate,
+    promotions: &[Promotion],
+    lines: &[PricedLine],
+    subtotal: Money,
+) -> Result<PromotionRun, PricingError> {
+    let currency = subtotal.currency;
+    let mut remaining = subtotal;
+    let mut run = PromotionRun::default();
+    let mut exclusive: Option<&str> = None;
+
+    for promotion in promotions {
+        let Some((reward, explanation)) = qualify(rate, promotion, lines, currency)? else { continue };
+        let reward = match reward {
+            Reward::Reduce(amount) => {
+                let amount = Money::new(amount.minor_units.min(remaining.minor_units), currency);
+                if amount.minor_units == 0 {
+                    continue;
+                }
+                Reward::Reduce(amount)
+            }
+            Reward::FreeShipping if run.free_shipping.is_some() => continue,
+            Reward::FreeShipping => Reward::FreeShipping,
+        };
+
+        let applied_any = !run.discounts.is_empty() || run.free_shipping.is_some();
+        let blocked = match exclusive {
+            Some(name) => Some(format!("{}; not combined with {}", explanation, name)),
+            None if !promotion.stackable && applied_any => {
+                Some(format!("{}; cannot be combined with other promotions", explanation))
+            }
+            None => None,
+        };
+        if let Some(explanation) = blocked {
+            run.outcomes.push(PromotionOutcome { promotion_id: promotion.id, name: promotion.name.clone(), applied: false, explanation });
+            continue;
+        }
+
+        match reward {
+            Reward::Reduce(amount) => {
+                remaining = remaining.checked_sub(amount)?;
+                run.discounts.push(Discount { label: promotion.name.clone(), amount, on_shipping: false });
+            }
+            Reward::FreeShipping => run.free_shipping = Some(promotion.name.clone()),
+        }
+        run.outcomes.push(PromotionOutcome { promotion_id: promotion.id, name: promotion.name.clone(), applied: true, explanation });
+        if !promotion.stackable {
+            exclusive = Some(&promotion.name);
+        }
+    }
+
+    Ok(run)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cart::fixtures::{line, line_of, product, promotion, subtotal};
+    use super::*;
+
+    fn buy_get(name: &str, buy: i32, get: i32, percent: i32) -> Promotion {
+        Promotion { buy_quantity: Some(buy), get_quantity: Some(get), get_percent_off: percent, ..promotion(name) }
+    }
+
+    fn spend(name: &str, threshold: i64, percent_off: Option<i32>, amount_off: Option<i64>) -> Promotion {
+        Promotion { threshold_amount: Some(threshold), percent_off, amount_off, ..promotion(name) }
+    }
+
+    fn run(promotions: &[Promotion], lines: &[PricedLine]) -> PromotionRun {
+        apply_promotions(&BaseRate::identity(Currency::Inr), promotions, lines, subtotal(lines)).unwrap()
+    }
+
+    fn amounts(run: &PromotionRun) -> Vec<i64> {
+        run.discounts.iter().map(|discount| discount.amount.minor_units).collect()
+    }
+
+    #[test]
+    fn buy_x_get_y_gives_away_the_cheapest_units() {
+        let lines = [line(1_000, 2), line(300, 1)];
+        let run = run(&[buy_get("3 for 2", 2, 1, 100)], &lines);
+        assert_eq!(amounts(&run), vec![300]);
+        assert_eq!(run.outcomes[0].explanation, "Buy 2 get 1 free: 1 item free");
+    }
+
+    #[test]
+    fn buy_x_get_y_counts_every_complete_set() {
+        let lines = [line(1_000, 5)];
+        let run = run(&[buy_get("Second half price", 1, 1, 50)], &lines);
+        assert_eq!(amounts(&run), vec![1_000]);
+        assert_eq!(run.outcomes[0].explanation, "Buy 1 get 1 at 50% off: 2 items discounted");
+    }
+
+    #[test]
+    fn buy_x_get_y_handles_large_quantities() {
+        let lines = [line(500, i32::MAX), line(200, 3)];
+        let run = run(&[buy_get("3 for 2", 2, 1, 100)], &lines);
+        let free = (i32::MAX as i64 + 3) / 3;
+        assert_eq!(amounts(&run), vec![3 * 200 + (free - 3) * 500]);
+    }
+
+    #[test]
+    fn spend_threshold_needs_the_eligible_spend() {
+        let promotions = [spend("10% over 50", 5_000, Some(10), None)];
+        assert!(run(&promotions, &[line(4_999, 1)]).outcomes.is_empty());
+        assert_eq!(amounts(&run(&promotions, &[line(2_500, 2)])), vec![500]);
+    }
+
+    #[test]
+    fn spend_threshold_amounts_are_converted_from_the_base_currency() {
+        let rate = BaseRate { currency: Currency::Usd, scaled: Some(120_000_000) };
+        let lines = [line_of(product(Money::new(60, Currency::Usd), None), 1)];
+        let run = apply_promotions(&rate, &[spend("Money off", 5_000, None, Some(1_000))], &lines, subtotal(&lines));
+        assert_eq!(amounts(&run.unwrap()), vec![12]);
+    }
+
+    #[test]
+    fn quantity_tier_uses_the_highest_tier_reached() {
+        let tiered = Promotion {
+            tiers: vec![
+                QuantityTier { min_quantity: 3, percent_off: 10 },
+                QuantityTier { min_quantity: 6, percent_off: 15 },
+            ],
+            ..promotion("Bulk")
+        };
+        assert!(run(std::slice::from_ref(&tiered), &[line(100, 2)]).outcomes.is_empty());
+        assert_eq!(amounts(&run(std::slice::from_ref(&tiered), &[line(100, 4)])), vec![40]);
+        assert_eq!(amounts(&run(&[tiered], &[line(100, 7)])), vec![105]);
+    }
+
+    #[test]
+    fn restricted_promotions_only_count_eligible_lines() {
+        let category = Uuid::new_v4();
+        let tiered = Promotion {
+            category_ids: vec![category],
+            tiers: vec![QuantityTier { min_quantity: 2, percent_off: 50 }],
+            ..promotion("Category deal")
+        };
+        let lines = [line_of(product(Money::new(200, Currency::Inr), Some(category)), 2), line(1_000, 5)];
+        assert_eq!(amounts(&run(&[tiered], &lines)), vec![200]);
+    }
+
+    #[test]
+    fn discounts_never_exceed_the_subtotal() {
+        let promotions = [spend("Big", 100, None, Some(700)), spend("Bigger", 100, None, Some(700))];
+        let run = run(&promotions, &[line(1_000, 1)]);
+        assert_eq!(amounts(&run), vec![700, 300]);
+    }
+
+    #[test]
+    fn non_stackable_promotion_is_left_out_after_another_applied() {
+        let exclusive = Promotion { stackable: false, ..spend("Exclusive", 100, Some(20), None) };
+        let run = run(&[buy_get("3 for 2", 2, 1, 100), exclusive], &[line(100, 3)]);
+        assert_eq!(amounts(&run), vec![100]);
+        assert!(!run.outcomes[1].applied);
+        assert!(run.outcomes[1].explanation.ends_with("; cannot be combined with other promotions"));
+    }
+
+    #[test]
+    fn non_stackable_promotion_blocks_the_ones_after_it() {
+        let exclusive = Promotion { stackable: false, ..spend("Exclusive", 100, Some(20), None) };
+        let run = run(&[exclusive, buy_get("3 for 2", 2, 1, 100)], &[line(100, 3)]);
+        assert_eq!(amounts(&run), vec![60]);
+        assert!(!run.outcomes[1].applied);
+        assert!(run.outcomes[1].explanation.ends_with("; not combined with Exclusive"));
+    }
+
+    #[test]
+    fn free_shipping_promotion_waives_shipping_once() {
+        let promotions = [spend("Ship free", 1_000, None, None), spend("Also free", 500, None, None)];
+        let run = run(&promotions, &[line(1_000, 1)]);
+        assert!(run.discounts.is_empty());
+        assert_eq!(run.free_shipping.as_deref(), Some("Ship free"));
+        assert_eq!(run.outcomes.len(), 1);
+        assert_eq!(run.outcomes[0].explanation, "Spend 10.00 INR or more: free shipping");
+    }
+}
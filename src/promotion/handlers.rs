@@ -0,0 +1,174 @@
+use actix_web::{web, HttpResponse, Responder};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::AdminUser;
+use crate::promotion::engine::attach_tiers;
+use crate::promotion::models::{Promotion, PromotionKind, PromotionUpdate};
+use crate::routes::models::ApiResponse;
+
+fn invalid_promotion(msg: &str) -> HttpResponse {
+    HttpResponse::BadRequest().json(ApiResponse {
+        status: "Error".to_string(),
+        msg: msg.to_string(),
+        data: "No Data".to_string(),
+    })
+}
+
+// Inserts when id is None
+async fn write_promotion(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Option<Uuid>,
+    payload: &PromotionUpdate,
+) -> Result<Option<Promotion>, sqlx::Error> {
+    let sql = match id {
+        None => "INSERT INTO promotions (name, kind, priority, stackable, buy_quantity, get_quantity, \
+                    get_percent_off, threshold_amount, percent_off, amount_off, product_ids, category_ids, \
+                    starts_at, ends_at, active) \
+                 VALUES ($2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16) RETURNING *",
+        Some(_) => "UPDATE promotions SET name = $2, kind = $3, priority = $4, stackable = $5, \
+                    buy_quantity = $6, get_quantity = $7, get_percent_off = $8, threshold_amount = $9, \
+                    percent_off = $10, amount_off = $11, product_ids = $12, category_ids = $13, \
+                    starts_at = $14, ends_at = $15, active = $16 \
+                 WHERE id = $1 RETURNING *",
+    };
+
+    let promotion = sqlx::query_as::<_, Promotion>(sql)
+        .bind(id)
+        .bind(payload.name.trim())
+        .bind(payload.kind.as_str())
+        .bind(payload.priority)
+        .bind(payload.stackable)
+        .bind(payload.buy_quantity)
+        .bind(payload.get_quantity)
+        .bind(payload.get_percent_off.unwrap_or(100))
+        .bind(payload.threshold_amount)
+        .bind(payload.percent_off)
+        .bind(payload.amount_off)
+        .bind(&payload.product_ids)
+        .bind(&payload.category_ids)
+        .bind(payload.starts_at)
+        .bind(payload.ends_at)
+        .bind(payload.active)
+        .fetch_optional(&mut **tx)
+        .await?;
+    let Some(mut promotion) = promotion else { return Ok(None) };
+
+    sqlx::query("DELETE FROM promotion_tiers WHERE promotion_id = $1")
+        .bind(promotion.id)
+        .execute(&mut **tx)
+        .await?;
+    let mut tiers = payload.tiers.clone();
+    tiers.sort_by_key(|tier| tier.min_quantity);
+    for tier in &tiers {
+        sqlx::query("INSERT INTO promotion_tiers (promotion_id, min_quantity, percent_off) VALUES ($1, $2, $3)")
+            .bind(promotion.id)
+            .bind(tier.min_quantity)
+            .bind(tier.percent_off)
+            .execute(&mut **tx)
+            .await?;
+    }
+    promotion.tiers = tiers;
+    Ok(Some(promotion))
+}
+
+async fn save_promotion(data: &AppState, id: Option<Uuid>, payload: &PromotionUpdate) -> HttpResponse {
+    match (payload.kind, payload.tiers.is_empty()) {
+        (PromotionKind::QuantityTier, true) => return invalid_promotion("Quantity tier promotions need at least one tier"),
+        (PromotionKind::BuyXGetY | PromotionKind::SpendThreshold | PromotionKind::FreeShipping, false) => {
+            return invalid_promotion("Only quantity tier promotions have tiers");
+        }
+        _ => {}
+    }
+
+    let mut tx = match data.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    match write_promotion(&mut tx, id, payload).await {
+        Ok(Some(promotion)) => match tx.commit().await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse {
+                status: "Success".to_string(),
+                msg: "Promotion saved".to_string(),
+                data: promotion,
+            }),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        },
+        Ok(None) => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Promotion not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(sqlx::Error::Database(e)) if e.is_check_violation() || e.is_unique_violation() => invalid_promotion(
+            "buy_x_get_y needs buy_quantity and get_quantity, spend_threshold needs threshold_amount and \
+             one of percent_off or amount_off, free_shipping needs only threshold_amount, tiers need \
+             distinct min_quantity above 1, percentages are 1-100 and ends_at must follow starts_at",
+        ),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn create_promotion(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    payload: web::Json<PromotionUpdate>,
+) -> impl Responder {
+    save_promotion(&data, None, &payload).await
+}
+
+pub async fn update_promotion(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    id: web::Path<Uuid>,
+    payload: web::Json<PromotionUpdate>,
+) -> impl Responder {
+    save_promotion(&data, Some(id.into_inner()), &payload).await
+}
+
+pub async fn get_promotions(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+) -> impl Responder {
+    let promotions = sqlx::query_as::<_, Promotion>("SELECT * FROM promotions ORDER BY priority DESC, created_at")
+        .fetch_all(&data.db_pool)
+        .await;
+    let mut promotions = match promotions {
+        Ok(promotions) => promotions,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    match attach_tiers(&data.db_pool, &mut promotions).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Promotions".to_string(),
+            data: promotions,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn delete_promotion(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    id: web::Path<Uuid>,
+) -> impl Responder {
+    let result = sqlx::query("DELETE FROM promotions WHERE id = $1")
+        .bind(id.into_inner())
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(result) if result.rows_affected() > 0 => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Promotion deleted".to_string(),
+            data: "{}".to_string(),
+        }),
+        Ok(_) => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Promotion not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
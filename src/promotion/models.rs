@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromotionKind {
+    BuyXGetY,
+    SpendThreshold,
+    QuantityTier,
+    FreeShipping,
+}
+
+impl PromotionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromotionKind::BuyXGetY => "buy_x_get_y",
+            PromotionKind::SpendThreshold => "spend_threshold",
+            PromotionKind::QuantityTier => "quantity_tier",
+            PromotionKind::FreeShipping => "free_shipping",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct QuantityTier {
+    pub min_quantity: i32,
+    pub percent_off: i32,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Promotion {
+    pub id: Uuid,
+    pub name: String,
+    pub kind: String,
+    pub priority: i32,
+    pub stackable: bool,
+    pub buy_quantity: Option<i32>,
+    pub get_quantity: Option<i32>,
+    pub get_percent_off: i32,
+    // Base currency minor units
+    pub threshold_amount: Option<i64>,
+    pub percent_off: Option<i32>,
+    // Base currency minor units
+    pub amount_off: Option<i64>,
+    pub product_ids: Vec<Uuid>,
+    pub category_ids: Vec<Uuid>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub ends_at: Option<DateTime<Utc>>,
+    pub active: bool,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    // Lowest first
+    #[sqlx(skip)]
+    pub tiers: Vec<QuantityTier>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PromotionUpdate {
+    pub name: String,
+    pub kind: PromotionKind,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default = "default_true")]
+    pub stackable: bool,
+    pub buy_quantity: Option<i32>,
+    pub get_quantity: Option<i32>,
+    pub get_percent_off: Option<i32>,
+    pub threshold_amount: Option<i64>,
+    pub percent_off: Option<i32>,
+    pub amount_off: Option<i64>,
+    #[serde(default)]
+    pub tiers: Vec<QuantityTier>,
+    #[serde(default)]
+    pub product_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub category_ids: Vec<Uuid>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(default = "default_true")]
+    pub active: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromotionOutcome {
+    pub promotion_id: Uuid,
+    pub name: String,
+    pub applied: bool,
+    // What qualified the cart, or why a qualifying promotion was left out
+    pub explanation: String,
+}
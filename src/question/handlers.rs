@@ -0,0 +1,288 @@
+use actix_web::{web, HttpResponse, Responder};
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::{AdminUser, AuthenticatedUser};
+use crate::question::listing::{approved_questions, ANSWER_COLUMNS, QUESTION_COLUMNS};
+use crate::question::models::{
+    Answer, ModerationQuery, ModerationQueue, ModerationStatus, NewPost, Question, QuestionListQuery, StatusUpdate,
+};
+use crate::routes::models::ApiResponse;
+
+fn empty_post() -> HttpResponse {
+    HttpResponse::BadRequest().json(ApiResponse {
+        status: "Error".to_string(),
+        msg: "Body is required".to_string(),
+        data: "No Data".to_string(),
+    })
+}
+
+pub async fn get_questions(
+    data: web::Data<AppState>,
+    product_id: web::Path<Uuid>,
+    query: web::Query<QuestionListQuery>,
+) -> impl Responder {
+    let per_page = query.per_page.unwrap_or(20).clamp(1, 100);
+    let page = query.page.unwrap_or(1).max(1);
+
+    match approved_questions(&data.db_pool, product_id.into_inner(), query.sort, per_page, (page - 1).saturating_mul(per_page)).await {
+        Ok(questions) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Product questions".to_string(),
+            data: questions,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn ask_question(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    product_id: web::Path<Uuid>,
+    payload: web::Json<NewPost>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+    if payload.body.trim().is_empty() {
+        return empty_post();
+    }
+
+    let question = sqlx::query_as::<_, Question>(&format!(
+        "WITH q AS ( \
+            INSERT INTO product_questions (product_id, user_id, body) VALUES ($1, $2, $3) RETURNING *) \
+         SELECT {} FROM q JOIN users u ON u.id = q.user_id",
+        QUESTION_COLUMNS
+    ))
+        .bind(product_id.into_inner())
+        .bind(user_id)
+        .bind(payload.body.trim())
+        .fetch_one(&data.db_pool)
+        .await;
+
+    match question {
+        Ok(question) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Question submitted for review".to_string(),
+            data: question,
+        }),
+        Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+// Staff answers go live at once; customers may answer only products they bought, and are moderated
+pub async fn answer_question(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    question_id: web::Path<Uuid>,
+    payload: web::Json<NewPost>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+    let question_id = question_id.into_inner();
+    if payload.body.trim().is_empty() {
+        return empty_post();
+    }
+
+    let is_staff = user.claims.role == "admin";
+    let purchased = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS ( \
+            SELECT 1 FROM orders o JOIN order_items oi ON oi.order_id = o.id \
+            WHERE o.user_id = $1 AND oi.product_id = q.product_id AND o.status = 'paid') \
+         FROM product_questions q WHERE q.id = $2 AND q.status = 'approved'"
+    )
+        .bind(user_id)
+        .bind(question_id)
+        .fetch_optional(&data.db_pool)
+        .await;
+
+    let (author_role, status) = match purchased {
+        Ok(Some(_)) if is_staff => ("staff", ModerationStatus::Approved),
+        Ok(Some(true)) => ("verified_buyer", ModerationStatus::Pending),
+        Ok(Some(false)) => return HttpResponse::Forbidden().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Only staff and customers who bought this product can answer".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Ok(None) => return HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Question not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let answer = sqlx::query_as::<_, Answer>(&format!(
+        "WITH a AS ( \
+            INSERT INTO product_answers (question_id, user_id, body, author_role, status) \
+            VALUES ($1, $2, $3, $4, $5) RETURNING *) \
+         SELECT {} FROM a JOIN users u ON u.id = a.user_id",
+        ANSWER_COLUMNS
+    ))
+        .bind(question_id)
+        .bind(user_id)
+        .bind(payload.body.trim())
+        .bind(author_role)
+        .bind(status.as_str())
+        .fetch_one(&data.db_pool)
+        .await;
+
+    match answer {
+        Ok(answer) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: match status {
+                ModerationStatus::Approved => "Answer published".to_string(),
+                _ => "Answer submitted for review".to_string(),
+            },
+            data: answer,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+// The counter only moves when the vote row is actually inserted
+async fn upvote(data: &AppState, user: &AuthenticatedUser, sql: &str, post_id: Uuid) -> HttpResponse {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let voted = sqlx::query(sql)
+        .bind(post_id)
+        .bind(user_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match voted {
+        Ok(result) if result.rows_affected() > 0 => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Upvoted".to_string(),
+            data: "{}".to_string(),
+        }),
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Conflict".to_string(),
+            msg: "Already voted, own post or post not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn upvote_question(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    question_id: web::Path<Uuid>,
+) -> impl Responder {
+    upvote(
+        &data,
+        &user,
+        "WITH vote AS ( \
+            INSERT INTO question_votes (question_id, user_id) \
+            SELECT id, $2 FROM product_questions WHERE id = $1 AND user_id <> $2 AND status = 'approved' \
+            ON CONFLICT DO NOTHING RETURNING question_id) \
+         UPDATE product_questions SET upvotes = upvotes + 1 WHERE id IN (SELECT question_id FROM vote)",
+        question_id.into_inner(),
+    ).await
+}
+
+pub async fn upvote_answer(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    answer_id: web::Path<Uuid>,
+) -> impl Responder {
+    upvote(
+        &data,
+        &user,
+        "WITH vote AS ( \
+            INSERT INTO answer_votes (answer_id, user_id) \
+            SELECT id, $2 FROM product_answers WHERE id = $1 AND user_id <> $2 AND status = 'approved' \
+            ON CONFLICT DO NOTHING RETURNING answer_id) \
+         UPDATE product_answers SET upvotes = upvotes + 1 WHERE id IN (SELECT answer_id FROM vote)",
+        answer_id.into_inner(),
+    ).await
+}
+
+pub async fn get_moderation_queue(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    query: web::Query<ModerationQuery>,
+) -> impl Responder {
+    let questions = sqlx::query_as::<_, Question>(&format!(
+        "SELECT {} FROM product_questions q JOIN users u ON u.id = q.user_id \
+         WHERE q.status = $1 ORDER BY q.created_at",
+        QUESTION_COLUMNS
+    ))
+        .bind(query.status.as_str())
+        .fetch_all(&data.db_pool)
+        .await;
+
+    let questions = match questions {
+        Ok(questions) => questions,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let answers = sqlx::query_as::<_, Answer>(&format!(
+        "SELECT {} FROM product_answers a JOIN users u ON u.id = a.user_id \
+         WHERE a.status = $1 ORDER BY a.created_at",
+        ANSWER_COLUMNS
+    ))
+        .bind(query.status.as_str())
+        .fetch_all(&data.db_pool)
+        .await;
+
+    match answers {
+        Ok(answers) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: format!("Questions and answers with status {}", query.status.as_str()),
+            data: ModerationQueue { questions, answers },
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+async fn set_status(data: &AppState, table: &str, post_id: Uuid, status: ModerationStatus) -> HttpResponse {
+    let result = sqlx::query(&format!("UPDATE {} SET status = $1 WHERE id = $2", table))
+        .bind(status.as_str())
+        .bind(post_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(done) if done.rows_affected() == 0 => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Post not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: format!("Marked as {}", status.as_str()),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn moderate_question(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    question_id: web::Path<Uuid>,
+    payload: web::Json<StatusUpdate>,
+) -> impl Responder {
+    set_status(&data, "product_questions", question_id.into_inner(), payload.status).await
+}
+
+pub async fn moderate_answer(
+    data: web::Data<AppState>,
+    _admin: AdminUser,
+    answer_id: web::Path<Uuid>,
+    payload: web::Json<StatusUpdate>,
+) -> impl Responder {
+    set_status(&data, "product_answers", answer_id.into_inner(), payload.status).await
+}
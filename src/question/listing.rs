@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::question::models::{Answer, Question, QuestionSort};
+
+pub const QUESTION_COLUMNS: &str = "q.id, q.product_id, u.username, q.body, q.status, q.upvotes, q.created_at";
+pub const ANSWER_COLUMNS: &str = "a.id, a.question_id, u.username, a.body, a.author_role, a.status, a.upvotes, a.created_at";
+
+pub async fn approved_questions(
+    pool: &PgPool,
+    product_id: Uuid,
+    sort: QuestionSort,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<Question>, sqlx::Error> {
+    let order_by = match sort {
+        QuestionSort::MostUpvoted => "q.upvotes DESC, q.created_at DESC",
+        QuestionSort::Newest => "q.created_at DESC",
+    };
+
+    let mut questions = sqlx::query_as::<_, Question>(&format!(
+        "SELECT {} FROM product_questions q JOIN users u ON u.id = q.user_id \
+         WHERE q.product_id = $1 AND q.status = 'approved' ORDER BY {} LIMIT $2 OFFSET $3",
+        QUESTION_COLUMNS, order_by
+    ))
+        .bind(product_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+    let question_ids: Vec<Uuid> = questions.iter().map(|q| q.id).collect();
+    let answers = sqlx::query_as::<_, Answer>(&format!(
+        "SELECT {} FROM product_answers a JOIN users u ON u.id = a.user_id \
+         WHERE a.question_id = ANY($1) AND a.status = 'approved' \
+         ORDER BY a.author_role = 'staff' DESC, a.upvotes DESC, a.created_at",
+        ANSWER_COLUMNS
+    ))
+        .bind(&question_ids)
+        .fetch_all(pool)
+        .await?;
+
+    let mut by_question: HashMap<Uuid, Vec<Answer>> = HashMap::new();
+    for answer in answers {
+        by_question.entry(answer.question_id).or_default().push(answer);
+    }
+    for question in &mut questions {
+        question.answers = by_question.remove(&question.id).unwrap_or_default();
+    }
+    Ok(questions)
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationStatus {
+    #[default]
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl ModerationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationStatus::Pending => "pending",
+            ModerationStatus::Approved => "approved",
+            ModerationStatus::Rejected => "rejected",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Question {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub username: String,
+    pub body: String,
+    pub status: String,
+    pub upvotes: i32,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    // Staff first, then by upvotes
+    #[sqlx(skip)]
+    pub answers: Vec<Answer>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Answer {
+    pub id: Uuid,
+    pub question_id: Uuid,
+    pub username: String,
+    pub body: String,
+    // staff or verified_buyer
+    pub author_role: String,
+    pub status: String,
+    pub upvotes: i32,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewPost {
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusUpdate {
+    pub status: ModerationStatus,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionSort {
+    #[default]
+    MostUpvoted,
+    Newest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuestionListQuery {
+    #[serde(default)]
+    pub sort: QuestionSort,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerationQuery {
+    #[serde(default)]
+    pub status: ModerationStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModerationQueue {
+    pub questions: Vec<Question>,
+    pub answers: Vec<Answer>,
+}
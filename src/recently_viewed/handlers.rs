@@ -0,0 +1,122 @@
+use actix_web::{web, HttpResponse, Responder};
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::AuthenticatedUser;
+use crate::locale::models::RequestLocale;
+use crate::pricing::models::RequestCurrency;
+use crate::product::handlers::product_details;
+use crate::product::models::Product;
+use crate::recently_viewed::models::{RecentlyViewed, TrackingPreference};
+use crate::routes::models::ApiResponse;
+
+pub async fn get_recently_viewed(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    currency: RequestCurrency,
+    locale: RequestLocale,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let tracking = sqlx::query_scalar::<_, bool>("SELECT track_recently_viewed FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(&data.db_pool)
+        .await;
+
+    let tracking = match tracking {
+        Ok(tracking) => tracking.unwrap_or(false),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let products = sqlx::query_as::<_, Product>(
+        "SELECT p.* FROM recently_viewed r JOIN products p ON p.id = r.product_id \
+         WHERE r.user_id = $1 AND p.available ORDER BY r.viewed_at DESC"
+    )
+        .bind(user_id)
+        .fetch_all(&data.db_pool)
+        .await;
+
+    let products = match products {
+        Ok(products) => products,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    match product_details(&data, products, currency.0, &locale.0).await {
+        Ok(products) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Recently viewed products".to_string(),
+            data: RecentlyViewed { tracking, products },
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn clear_recently_viewed(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let result = sqlx::query("DELETE FROM recently_viewed WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Recently viewed products cleared".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+// Turning tracking off also forgets what was recorded so far
+pub async fn set_tracking(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    payload: web::Json<TrackingPreference>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let mut tx = match data.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    if let Err(e) = sqlx::query("UPDATE users SET track_recently_viewed = $1 WHERE id = $2")
+        .bind(payload.enabled)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+    {
+        return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+    }
+
+    if !payload.enabled
+        && let Err(e) = sqlx::query("DELETE FROM recently_viewed WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+    {
+        return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+    }
+
+    match tx.commit().await {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Recently viewed preference saved".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use crate::product::models::ProductDetails;
+
+#[derive(Debug, Serialize)]
+pub struct RecentlyViewed {
+    pub tracking: bool,
+    pub products: Vec<ProductDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrackingPreference {
+    pub enabled: bool,
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::env;
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+// Past this, new views are dropped rather than slowing product reads down
+const QUEUE_CAPACITY: usize = 4096;
+const BATCH_SIZE: usize = 256;
+
+type View = (Uuid, Uuid, DateTime<Utc>);
+
+// Recording never waits on the database
+#[derive(Clone)]
+pub struct ViewRecorder {
+    sender: mpsc::Sender<View>,
+}
+
+impl ViewRecorder {
+    pub fn record(&self, user_id: Uuid, product_id: Uuid) {
+        if self.sender.try_send((user_id, product_id, Utc::now())).is_err() {
+            debug!("Recently viewed queue is full, dropping a view of {}", product_id);
+        }
+    }
+}
+
+pub fn spawn_view_recorder(pool: PgPool) -> ViewRecorder {
+    let limit = env::var("RECENTLY_VIEWED_LIMIT")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|limit| *limit > 0)
+        .unwrap_or(20);
+    let (sender, mut receiver) = mpsc::channel::<View>(QUEUE_CAPACITY);
+
+    actix_rt::spawn(async move {
+        while let Some(first) = receiver.recv().await {
+            let mut batch = vec![first];
+            while batch.len() < BATCH_SIZE {
+                match receiver.try_recv() {
+                    Ok(view) => batch.push(view),
+                    Err(_) => break,
+                }
+            }
+            if let Err(e) = save_views(&pool, batch, limit).await {
+                warn!("Failed to record product views: {}", e);
+            }
+        }
+    });
+
+    ViewRecorder { sender }
+}
+
+async fn save_views(pool: &PgPool, batch: Vec<View>, limit: i64) -> Result<(), sqlx::Error> {
+    // One row per user and product, or the upsert would touch a row twice
+    let mut latest: HashMap<(Uuid, Uuid), DateTime<Utc>> = HashMap::new();
+    for (user_id, product_id, viewed_at) in batch {
+        let entry = latest.entry((user_id, product_id)).or_insert(viewed_at);
+        *entry = (*entry).max(viewed_at);
+    }
+
+    let mut user_ids = Vec::with_capacity(latest.len());
+    let mut product_ids = Vec::with_capacity(latest.len());
+    let mut viewed_ats = Vec::with_capacity(latest.len());
+    for ((user_id, product_id), viewed_at) in latest {
+        user_ids.push(user_id);
+        product_ids.push(product_id);
+        viewed_ats.push(viewed_at);
+    }
+
+    // Users who opted out and products that do not exist are skipped here
+    sqlx::query(
+        "INSERT INTO recently_viewed (user_id, product_id, viewed_at) \
+         SELECT v.user_id, v.product_id, v.viewed_at \
+         FROM UNNEST($1::UUID[], $2::UUID[], $3::TIMESTAMPTZ[]) AS v(user_id, product_id, viewed_at) \
+         JOIN users u ON u.id = v.user_id AND u.track_recently_viewed \
+         JOIN products p ON p.id = v.product_id \
+         ON CONFLICT (user_id, product_id) \
+         DO UPDATE SET viewed_at = GREATEST(recently_viewed.viewed_at, EXCLUDED.viewed_at)"
+    )
+        .bind(&user_ids)
+        .bind(&product_ids)
+        .bind(&viewed_ats)
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        "DELETE FROM recently_viewed r USING ( \
+             SELECT user_id, product_id, \
+                    row_number() OVER (PARTITION BY user_id ORDER BY viewed_at DESC) AS position \
+             FROM recently_viewed WHERE user_id = ANY($1) \
+         ) ranked \
+         WHERE r.user_id = ranked.user_id AND r.product_id = ranked.product_id AND ranked.position > $2"
+    )
+        .bind(&user_ids)
+        .bind(limit)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use actix_web::{web, HttpResponse, Responder};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::AuthenticatedUser;
+use crate::review::models::{NewReview, Review, ReviewListQuery, ReviewSort};
+use crate::routes::models::ApiResponse;
+
+const REVIEW_COLUMNS: &str = "r.id, r.product_id, u.username, r.rating, r.title, r.body, r.helpful_count, r.created_at, r.updated_at";
+
+async fn refresh_rating(tx: &mut Transaction<'_, Postgres>, product_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE products SET \
+            rating_average = COALESCE((SELECT AVG(rating)::float8 FROM reviews WHERE product_id = $1), 0), \
+            rating_count = (SELECT COUNT(*) FROM reviews WHERE product_id = $1) \
+         WHERE id = $1"
+    )
+        .bind(product_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+pub async fn create_review(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    product_id: web::Path<Uuid>,
+    payload: web::Json<NewReview>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+    let product_id = product_id.into_inner();
+
+    if !(1..=5).contains(&payload.rating) {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Rating must be between 1 and 5".to_string(),
+            data: "No Data".to_string(),
+        });
+    }
+    if payload.title.trim().is_empty() || payload.body.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Title and body are required".to_string(),
+            data: "No Data".to_string(),
+        });
+    }
+
+    // Only customers who actually paid for the product may review it
+    let purchased = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS ( \
+            SELECT 1 FROM orders o JOIN order_items oi ON oi.order_id = o.id \
+            WHERE o.user_id = $1 AND oi.product_id = $2 AND o.status = 'paid')"
+    )
+        .bind(user_id)
+        .bind(product_id)
+        .fetch_one(&data.db_pool)
+        .await;
+
+    match purchased {
+        Ok(true) => {}
+        Ok(false) => return HttpResponse::Forbidden().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Only customers who bought this product can review it".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+
+    let mut tx = match data.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    // A second review from the same customer replaces the first one
+    let review_id = sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO reviews (product_id, user_id, rating, title, body) VALUES ($1, $2, $3, $4, $5) \
+         ON CONFLICT (product_id, user_id) DO UPDATE \
+         SET rating = EXCLUDED.rating, title = EXCLUDED.title, body = EXCLUDED.body, updated_at = now() \
+         RETURNING id"
+    )
+        .bind(product_id)
+        .bind(user_id)
+        .bind(payload.rating)
+        .bind(payload.title.trim())
+        .bind(payload.body.trim())
+        .fetch_one(&mut *tx)
+        .await;
+
+    let review_id = match review_id {
+        Ok(id) => id,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    if let Err(e) = refresh_rating(&mut tx, product_id).await {
+        return HttpResponse::InternalServerError().body(format!("Error: {}", e));
+    }
+
+    let review = sqlx::query_as::<_, Review>(&format!(
+        "SELECT {} FROM reviews r JOIN users u ON u.id = r.user_id WHERE r.id = $1",
+        REVIEW_COLUMNS
+    ))
+        .bind(review_id)
+        .fetch_one(&mut *tx)
+        .await;
+
+    match review {
+        Ok(review) => match tx.commit().await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse {
+                status: "Success".to_string(),
+                msg: "Review saved".to_string(),
+                data: review,
+            }),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        },
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn get_reviews(
+    data: web::Data<AppState>,
+    product_id: web::Path<Uuid>,
+    query: web::Query<ReviewListQuery>,
+) -> impl Responder {
+    let per_page = query.per_page.unwrap_or(20).clamp(1, 100);
+    let page = query.page.unwrap_or(1).max(1);
+    let order_by = match query.sort {
+        ReviewSort::Newest => "r.created_at DESC",
+        ReviewSort::MostHelpful => "r.helpful_count DESC, r.created_at DESC",
+    };
+
+    let reviews = sqlx::query_as::<_, Review>(&format!(
+        "SELECT {} FROM reviews r JOIN users u ON u.id = r.user_id \
+         WHERE r.product_id = $1 ORDER BY {} LIMIT $2 OFFSET $3",
+        REVIEW_COLUMNS, order_by
+    ))
+        .bind(product_id.into_inner())
+        .bind(per_page)
+        .bind((page - 1).saturating_mul(per_page))
+        .fetch_all(&data.db_pool)
+        .await;
+
+    match reviews {
+        Ok(reviews) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Product reviews".to_string(),
+            data: reviews,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn vote_helpful(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    review_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    // One vote per user, never on their own review; the counter only moves
+    // when the vote row is actually inserted.
+    let voted = sqlx::query(
+        "WITH vote AS ( \
+            INSERT INTO review_votes (review_id, user_id) \
+            SELECT id, $2 FROM reviews WHERE id = $1 AND user_id <> $2 \
+            ON CONFLICT DO NOTHING RETURNING review_id) \
+         UPDATE reviews SET helpful_count = helpful_count + 1 WHERE id IN (SELECT review_id FROM vote)"
+    )
+        .bind(review_id.into_inner())
+        .bind(user_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match voted {
+        Ok(result) if result.rows_affected() > 0 => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Marked as helpful".to_string(),
+            data: "{}".to_string(),
+        }),
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Conflict".to_string(),
+            msg: "Already voted, own review or review not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
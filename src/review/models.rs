@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Review {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub username: String,
+    pub rating: i16,
+    pub title: String,
+    pub body: String,
+    pub helpful_count: i32,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewReview {
+    pub rating: i16,
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewSort {
+    #[default]
+    Newest,
+    MostHelpful,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewListQuery {
+    #[serde(default)]
+    pub sort: ReviewSort,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
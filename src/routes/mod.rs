@@ -6,19 +6,114 @@ use crate::auth::handlers as auth_handlers;
 use crate::product::handlers as product_handlers;
 use crate::cart::handlers as cart_handlers;
 use crate::order::handlers as order_handlers;
+use crate::review::handlers as review_handlers;
+use crate::pricing::handlers as pricing_handlers;
+use crate::catalogue::handlers as catalogue_handlers;
+use crate::category::handlers as category_handlers;
+use crate::wishlist::handlers as wishlist_handlers;
+use crate::alert::handlers as alert_handlers;
+use crate::bundle::handlers as bundle_handlers;
+use crate::locale::handlers as locale_handlers;
+use crate::digital::handlers as digital_handlers;
+use crate::recently_viewed::handlers as recently_viewed_handlers;
+use crate::question::handlers as question_handlers;
+use crate::coupon::handlers as coupon_handlers;
+use crate::promotion::handlers as promotion_handlers;
+use crate::cart_recovery::handlers as cart_recovery_handlers;
 
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg
         .route("/product/all", web::get().to(product_handlers::get_all_products))   // Get all products
         .route("/product/{id}", web::get().to(product_handlers::get_product_by_id)) // Get product by ID
+        .route("/product/{id}/related", web::get().to(product_handlers::get_related_products)) // Frequently bought together
+        .route("/categories", web::get().to(category_handlers::get_categories))     // List categories
+        .route("/admin/categories/{slug}", web::put().to(category_handlers::save_category)) // Create or rename a category
+        .route("/admin/categories/{slug}/translations/{locale}", web::put().to(locale_handlers::save_category_translation)) // Translate a category
+        .route("/admin/categories/{slug}/translations/{locale}", web::delete().to(locale_handlers::delete_category_translation)) // Remove a category translation
+        .route("/admin/product/{id}/translations", web::get().to(locale_handlers::get_product_translations)) // List product translations
+        .route("/admin/product/{id}/translations/{locale}", web::put().to(locale_handlers::save_product_translation)) // Translate a product
+        .route("/admin/product/{id}/translations/{locale}", web::delete().to(locale_handlers::delete_product_translation)) // Remove a product translation
+        .route("/admin/product/{id}/category", web::put().to(category_handlers::set_product_category)) // Set product category
+        .route("/attributes", web::get().to(product_handlers::get_attribute_definitions)) // List attribute definitions
+        .route("/admin/attributes/{key}", web::put().to(product_handlers::define_attribute)) // Define an attribute
+        .route("/admin/product/{id}/attributes", web::put().to(product_handlers::set_product_attributes)) // Set product attributes
+        .route("/product/{id}/reviews", web::get().to(review_handlers::get_reviews))     // List product reviews
+        .route("/product/{id}/reviews", web::post().to(review_handlers::create_review))  // Review a purchased product
+        .route("/reviews/{id}/helpful", web::post().to(review_handlers::vote_helpful))   // Vote a review helpful
+        .route("/product/{id}/questions", web::get().to(question_handlers::get_questions)) // List answered questions
+        .route("/product/{id}/questions", web::post().to(question_handlers::ask_question)) // Ask about a product
+        .route("/questions/{id}/answers", web::post().to(question_handlers::answer_question)) // Answer as staff or buyer
+        .route("/questions/{id}/upvote", web::post().to(question_handlers::upvote_question)) // Upvote a question
+        .route("/answers/{id}/upvote", web::post().to(question_handlers::upvote_answer)) // Upvote an answer
+        .route("/admin/coupons", web::get().to(coupon_handlers::get_coupons)) // List coupons with usage
+        .route("/admin/coupons/{code}", web::put().to(coupon_handlers::save_coupon)) // Create or replace a coupon
+        .route("/admin/coupons/{code}", web::delete().to(coupon_handlers::deactivate_coupon)) // Deactivate a coupon
+        .route("/admin/cart-recovery", web::get().to(cart_recovery_handlers::get_recovery_stats)) // Abandoned cart recovery stats
+        .route("/admin/promotions", web::get().to(promotion_handlers::get_promotions)) // List automatic promotions
+        .route("/admin/promotions", web::post().to(promotion_handlers::create_promotion)) // Create a promotion
+        .route("/admin/promotions/{id}", web::put().to(promotion_handlers::update_promotion)) // Replace a promotion
+        .route("/admin/promotions/{id}", web::delete().to(promotion_handlers::delete_promotion)) // Delete a promotion
+        .route("/admin/questions", web::get().to(question_handlers::get_moderation_queue)) // Q&A moderation queue
+        .route("/admin/questions/{id}/status", web::put().to(question_handlers::moderate_question)) // Approve or reject a question
+        .route("/admin/answers/{id}/status", web::put().to(question_handlers::moderate_answer)) // Approve or reject an answer
+        .route("/admin/product/{id}/images", web::post().to(product_handlers::upload_product_images))   // Upload product images
+        .route("/admin/product/{id}/images/order", web::put().to(product_handlers::reorder_product_images)) // Reorder product images
+        .route("/admin/product/{id}/images/{image_id}", web::delete().to(product_handlers::delete_product_image)) // Delete a product image
+        .route("/admin/product/{id}/files", web::post().to(digital_handlers::upload_product_files)) // Attach downloadable files
+        .route("/admin/product/{id}/files", web::get().to(digital_handlers::get_product_files)) // List downloadable files
+        .route("/admin/product/{id}/files/{file_id}", web::delete().to(digital_handlers::delete_product_file)) // Delete a downloadable file
+        .route("/admin/product", web::post().to(product_handlers::create_product))  // Create a product
+        .route("/admin/product/{id}", web::put().to(product_handlers::update_product)) // Update a product
+        .route("/admin/product/{id}/history", web::get().to(product_handlers::get_product_history)) // Product change history
+        .route("/admin/product/{id}/revert/{version}", web::post().to(product_handlers::revert_product)) // Revert to a version
+        .route("/admin/products/export", web::get().to(catalogue_handlers::export_catalogue)) // Export catalogue
+        .service(
+            web::resource("/admin/products/import")                                  // Import catalogue
+                .app_data(web::PayloadConfig::new(50 * 1024 * 1024))
+                .route(web::post().to(catalogue_handlers::import_catalogue)),
+        )
+        .route("/admin/product/{id}/prices/{currency}", web::put().to(pricing_handlers::set_product_price)) // Set a price list entry
+        .route("/admin/product/{id}/components", web::put().to(bundle_handlers::set_bundle_components)) // Set bundle components
+        .route("/admin/product/{id}/sales", web::post().to(pricing_handlers::create_sale)) // Schedule a sale price
+        .route("/admin/product/{id}/sales", web::get().to(pricing_handlers::get_sales)) // List sale windows
+        .route("/admin/product/{id}/sales/{sale_id}", web::delete().to(pricing_handlers::delete_sale)) // Cancel a sale
+        .route("/admin/exchange-rates/{currency}", web::put().to(pricing_handlers::set_exchange_rate)) // Set an FX rate
+        .route("/me/currency", web::put().to(pricing_handlers::set_currency_preference)) // Save preferred currency
+        .route("/me/recently-viewed", web::get().to(recently_viewed_handlers::get_recently_viewed)) // Recently viewed products
+        .route("/me/recently-viewed", web::delete().to(recently_viewed_handlers::clear_recently_viewed)) // Clear recently viewed
+        .route("/me/recently-viewed/tracking", web::put().to(recently_viewed_handlers::set_tracking)) // Opt in or out of tracking
+        .route("/me/email", web::put().to(alert_handlers::set_email))              // Save email address
+        .route("/product/{id}/alerts", web::post().to(alert_handlers::subscribe))  // Back-in-stock / price-drop alert
+        .route("/me/alerts", web::get().to(alert_handlers::get_my_alerts))         // List my alerts
+        .route("/me/alerts/{id}", web::delete().to(alert_handlers::delete_alert))  // Remove an alert
+        .route("/alerts/unsubscribe/{token}", web::get().to(alert_handlers::unsubscribe)) // Unsubscribe link from emails
+        .route("/media/{key:.*}", web::get().to(product_handlers::serve_media))     // Serve stored media
         .route("/search", web::get().to(product_handlers::search_product))          // Change to GET for search
+        .route("/search/suggest", web::get().to(product_handlers::suggest_products)) // Search type-ahead
         .route("/create_cart", web::get().to(cart_handlers::create_cart))           // Create a new cart
         .route("/addToCart", web::post().to(cart_handlers::add_to_cart))            // Add product to cart
         .route("/myCart", web::get().to(cart_handlers::get_cart_items))             // Get cart items
+        .route("/cart/coupon", web::post().to(coupon_handlers::apply_coupon))      // Apply a coupon code
+        .route("/cart/coupon", web::delete().to(coupon_handlers::remove_coupon))   // Remove the coupon code
+        .route("/cart/restore/{token}", web::get().to(cart_recovery_handlers::restore_cart)) // Restore link from a reminder
+        .route("/cart/reminders/unsubscribe/{token}", web::get().to(cart_recovery_handlers::unsubscribe_reminders)) // Stop cart reminders
         .route("/flushCart", web::get().to(cart_handlers::clean_cart))              // Clean cart
         .route("/removeItem-cart", web::get().to(cart_handlers::remove_product_from_cart))// Fixed typo
+        .route("/wishlists", web::get().to(wishlist_handlers::get_wishlists))       // List my wishlists
+        .route("/wishlists", web::post().to(wishlist_handlers::create_wishlist))    // Create a named wishlist
+        .route("/wishlists/shared/{token}", web::get().to(wishlist_handlers::get_shared_wishlist)) // Public shared wishlist
+        .route("/wishlists/{id}", web::get().to(wishlist_handlers::get_wishlist))   // Wishlist with products
+        .route("/wishlists/{id}", web::delete().to(wishlist_handlers::delete_wishlist)) // Delete a wishlist
+        .route("/wishlists/{id}/items", web::post().to(wishlist_handlers::add_to_wishlist)) // Save a product
+        .route("/wishlists/{id}/items/{product_id}", web::delete().to(wishlist_handlers::remove_from_wishlist)) // Remove a product
+        .route("/wishlists/{id}/items/{product_id}/move-to-cart", web::post().to(wishlist_handlers::move_to_cart)) // Move a product to the cart
+        .route("/wishlists/{id}/share", web::post().to(wishlist_handlers::share_wishlist)) // Create a share link
+        .route("/wishlists/{id}/share", web::delete().to(wishlist_handlers::unshare_wishlist)) // Revoke the share link
         .route("/checkout", web::get().to(order_handlers::create_checkout))         // Checkout route
+        .route("/me/orders", web::get().to(order_handlers::get_my_orders))          // My orders with download links
+        .route("/downloads/{grant_id}/{file_id}", web::get().to(digital_handlers::download)) // Signed digital download
+        .route("/webhook/stripe", web::post().to(order_handlers::stripe_webhook))   // Stripe payment events
         .route("/signUp", web::post().to(auth_handlers::sign_up))                   // Sign up route
         .route("/logIn", web::post().to(auth_handlers::log_in));                                     // Log in route
 }
@@ -0,0 +1,65 @@
+use std::env;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use async_trait::async_trait;
+use crate::storage::{BlobStore, StorageError};
+
+pub struct LocalBlobStore {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: PathBuf, base_url: String) -> Self {
+        Self { root, base_url: base_url.trim_end_matches('/').to_string() }
+    }
+
+    pub fn from_env() -> Self {
+        let root = env::var("MEDIA_ROOT").unwrap_or_else(|_| "./media".to_string());
+        let base_url = env::var("MEDIA_BASE_URL").unwrap_or_else(|_| "http://localhost:8080/media".to_string());
+        Self::new(PathBuf::from(root), base_url)
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf, StorageError> {
+        // Keys come from URLs for reads, so never let them escape the root.
+        let relative = Path::new(key);
+        if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(StorageError::NotFound);
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+fn io_error(e: std::io::Error) -> StorageError {
+    if e.kind() == ErrorKind::NotFound {
+        StorageError::NotFound
+    } else {
+        StorageError::Backend(e.to_string())
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>, _content_type: &str) -> Result<(), StorageError> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+        }
+        tokio::fs::write(path, bytes).await.map_err(io_error)
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        tokio::fs::read(self.path_for(key)?).await.map_err(io_error)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path_for(key)?).await.map_err(io_error) {
+            Err(StorageError::NotFound) => Ok(()),
+            result => result,
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.base_url, key)
+    }
+}
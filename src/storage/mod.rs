@@ -0,0 +1,46 @@
+pub mod local;
+pub mod s3;
+
+use std::env;
+use std::sync::Arc;
+use async_trait::async_trait;
+
+#[derive(Debug)]
+pub enum StorageError {
+    NotFound,
+    Backend(String),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::NotFound => write!(f, "Blob not found"),
+            StorageError::Backend(e) => write!(f, "Storage error: {}", e),
+        }
+    }
+}
+
+// Keys are relative paths such as products/{product_id}/{image_id}/original.png
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<(), StorageError>;
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError>;
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+    fn url(&self, key: &str) -> String;
+}
+
+pub fn from_env() -> Arc<dyn BlobStore> {
+    match env::var("BLOB_STORE").unwrap_or_else(|_| "local".to_string()).as_str() {
+        "s3" => Arc::new(s3::S3BlobStore::from_env()),
+        _ => Arc::new(local::LocalBlobStore::from_env()),
+    }
+}
+
+pub fn content_type_for(key: &str) -> &'static str {
+    match key.rsplit('.').next() {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,81 @@
+use std::env;
+use async_trait::async_trait;
+use object_store::aws::{AmazonS3, AmazonS3Builder};
+use object_store::path::Path;
+use object_store::{Attribute, Attributes, ObjectStore, PutOptions, PutPayload};
+use crate::storage::{BlobStore, StorageError};
+
+// Any S3-compatible bucket, such as MinIO for local development
+pub struct S3BlobStore {
+    store: AmazonS3,
+    public_url: String,
+}
+
+impl S3BlobStore {
+    pub fn from_env() -> Self {
+        let bucket = env::var("S3_BUCKET").expect("S3_BUCKET must be set");
+        let mut builder = AmazonS3Builder::new()
+            .with_bucket_name(&bucket)
+            .with_region(env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()))
+            .with_access_key_id(env::var("S3_ACCESS_KEY").expect("S3_ACCESS_KEY must be set"))
+            .with_secret_access_key(env::var("S3_SECRET_KEY").expect("S3_SECRET_KEY must be set"));
+
+        let endpoint = env::var("S3_ENDPOINT").ok();
+        if let Some(endpoint) = &endpoint {
+            // MinIO and most self-hosted stores only speak path-style requests.
+            builder = builder
+                .with_endpoint(endpoint)
+                .with_allow_http(endpoint.starts_with("http://"))
+                .with_virtual_hosted_style_request(false);
+        }
+
+        let public_url = env::var("S3_PUBLIC_URL").unwrap_or_else(|_| match &endpoint {
+            Some(endpoint) => format!("{}/{}", endpoint.trim_end_matches('/'), bucket),
+            None => format!("https://{}.s3.amazonaws.com", bucket),
+        });
+
+        Self {
+            store: builder.build().expect("Failed to configure S3 blob store"),
+            public_url: public_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+fn backend_error(e: object_store::Error) -> StorageError {
+    match e {
+        object_store::Error::NotFound { .. } => StorageError::NotFound,
+        e => StorageError::Backend(e.to_string()),
+    }
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<(), StorageError> {
+        let mut attributes = Attributes::new();
+        attributes.insert(Attribute::ContentType, content_type.to_string().into());
+        let options = PutOptions { attributes, ..Default::default() };
+
+        self.store
+            .put_opts(&Path::from(key), PutPayload::from(bytes), options)
+            .await
+            .map(|_| ())
+            .map_err(backend_error)
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let result = self.store.get(&Path::from(key)).await.map_err(backend_error)?;
+        let bytes = result.bytes().await.map_err(backend_error)?;
+        Ok(bytes.to_vec())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match self.store.delete(&Path::from(key)).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(backend_error(e)),
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url, key)
+    }
+}
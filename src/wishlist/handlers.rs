@@ -0,0 +1,410 @@
+use actix_web::{web, HttpResponse, Responder};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::AppState;
+use crate::auth::models::AuthenticatedUser;
+use crate::cart::items::{add_item, cart_for_user};
+use crate::cart::models::CartError;
+use crate::pricing::models::{PricingError, RequestCurrency};
+use crate::locale::models::RequestLocale;
+use crate::money::Currency;
+use crate::product::handlers::product_details;
+use crate::product::models::Product;
+use crate::routes::models::ApiResponse;
+use crate::wishlist::models::{MoveToCart, NewWishlist, ShareLink, Wishlist, WishlistItem, WishlistView};
+
+const WISHLIST_SELECT: &str = "SELECT w.id, w.name, w.share_token, w.created_at, \
+    (SELECT COUNT(*) FROM wishlist_items wi WHERE wi.wishlist_id = w.id) AS item_count \
+    FROM wishlists w";
+
+async fn owned_wishlist(pool: &PgPool, wishlist_id: Uuid, user_id: Uuid) -> Result<Option<Wishlist>, sqlx::Error> {
+    sqlx::query_as::<_, Wishlist>(&format!("{} WHERE w.id = $1 AND w.user_id = $2", WISHLIST_SELECT))
+        .bind(wishlist_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+}
+
+async fn wishlist_view(
+    data: &AppState,
+    wishlist: Wishlist,
+    currency: Currency,
+    locale: &str,
+) -> Result<WishlistView, PricingError> {
+    let products = sqlx::query_as::<_, Product>(
+        "SELECT p.* FROM wishlist_items wi JOIN products p ON p.id = wi.product_id \
+         WHERE wi.wishlist_id = $1 ORDER BY wi.added_at DESC"
+    )
+        .bind(wishlist.id)
+        .fetch_all(&data.db_pool)
+        .await?;
+
+    let products = product_details(data, products, currency, locale).await?;
+    Ok(WishlistView { wishlist, products })
+}
+
+fn wishlist_not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(ApiResponse {
+        status: "Error".to_string(),
+        msg: "Wishlist not found".to_string(),
+        data: "No Data".to_string(),
+    })
+}
+
+pub async fn get_wishlists(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let wishlists = sqlx::query_as::<_, Wishlist>(&format!("{} WHERE w.user_id = $1 ORDER BY w.created_at", WISHLIST_SELECT))
+        .bind(user_id)
+        .fetch_all(&data.db_pool)
+        .await;
+
+    match wishlists {
+        Ok(wishlists) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Your wishlists".to_string(),
+            data: wishlists,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn create_wishlist(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    payload: web::Json<NewWishlist>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let wishlist = sqlx::query_as::<_, Wishlist>(
+        "INSERT INTO wishlists (user_id, name) VALUES ($1, $2) \
+         RETURNING id, name, share_token, created_at, 0::BIGINT AS item_count"
+    )
+        .bind(user_id)
+        .bind(payload.name.trim())
+        .fetch_one(&data.db_pool)
+        .await;
+
+    match wishlist {
+        Ok(wishlist) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Wishlist created".to_string(),
+            data: wishlist,
+        }),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() || e.is_check_violation() => {
+            HttpResponse::BadRequest().json(ApiResponse {
+                status: "Error".to_string(),
+                msg: "Wishlist names must be non-empty and unique".to_string(),
+                data: "No Data".to_string(),
+            })
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn delete_wishlist(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    wishlist_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let result = sqlx::query("DELETE FROM wishlists WHERE id = $1 AND user_id = $2")
+        .bind(wishlist_id.into_inner())
+        .bind(user_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(done) if done.rows_affected() == 0 => wishlist_not_found(),
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Wishlist deleted".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn get_wishlist(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    wishlist_id: web::Path<Uuid>,
+    currency: RequestCurrency,
+    locale: RequestLocale,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let wishlist = match owned_wishlist(&data.db_pool, wishlist_id.into_inner(), user_id).await {
+        Ok(Some(wishlist)) => wishlist,
+        Ok(None) => return wishlist_not_found(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    match wishlist_view(&data, wishlist, currency.0, &locale.0).await {
+        Ok(view) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Wishlist".to_string(),
+            data: view,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn add_to_wishlist(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    wishlist_id: web::Path<Uuid>,
+    payload: web::Json<WishlistItem>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let wishlist_id = wishlist_id.into_inner();
+    match owned_wishlist(&data.db_pool, wishlist_id, user_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return wishlist_not_found(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+
+    let result = sqlx::query(
+        "INSERT INTO wishlist_items (wishlist_id, product_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+    )
+        .bind(wishlist_id)
+        .bind(payload.product_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Product saved to wishlist".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn remove_from_wishlist(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<(Uuid, Uuid)>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+    let (wishlist_id, product_id) = path.into_inner();
+
+    let result = sqlx::query(
+        "DELETE FROM wishlist_items wi USING wishlists w \
+         WHERE wi.wishlist_id = w.id AND w.id = $1 AND w.user_id = $2 AND wi.product_id = $3"
+    )
+        .bind(wishlist_id)
+        .bind(user_id)
+        .bind(product_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(done) if done.rows_affected() == 0 => HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found in wishlist".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Product removed from wishlist".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn move_to_cart(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<(Uuid, Uuid)>,
+    payload: web::Json<MoveToCart>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+    let (wishlist_id, product_id) = path.into_inner();
+    let quantity = payload.quantity.unwrap_or(1);
+
+    if quantity < 1 {
+        return HttpResponse::BadRequest().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Quantity must be at least 1".to_string(),
+            data: "No Data".to_string(),
+        });
+    }
+
+    let mut tx = match data.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    let removed = sqlx::query(
+        "DELETE FROM wishlist_items wi USING wishlists w \
+         WHERE wi.wishlist_id = w.id AND w.id = $1 AND w.user_id = $2 AND wi.product_id = $3"
+    )
+        .bind(wishlist_id)
+        .bind(user_id)
+        .bind(product_id)
+        .execute(&mut *tx)
+        .await;
+
+    match removed {
+        Ok(done) if done.rows_affected() == 0 => return HttpResponse::NotFound().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: "Product not found in wishlist".to_string(),
+            data: "No Data".to_string(),
+        }),
+        Ok(_) => {}
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+
+    let cart_id = match cart_for_user(&mut tx, user_id).await {
+        Ok(cart_id) => cart_id,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    // Same rules as adding to the cart directly
+    match add_item(&mut tx, cart_id, product_id, quantity).await {
+        Ok(_) => {}
+        Err(CartError::Db(e)) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        Err(e) => return HttpResponse::Conflict().json(ApiResponse {
+            status: "Error".to_string(),
+            msg: e.to_string(),
+            data: "No Data".to_string(),
+        }),
+    }
+
+    match tx.commit().await {
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Product moved to cart".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn share_wishlist(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    wishlist_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    // Sharing twice keeps the existing link
+    let token = sqlx::query_scalar::<_, String>(
+        "UPDATE wishlists SET share_token = COALESCE(share_token, $3) \
+         WHERE id = $1 AND user_id = $2 RETURNING share_token"
+    )
+        .bind(wishlist_id.into_inner())
+        .bind(user_id)
+        .bind(Uuid::new_v4().simple().to_string())
+        .fetch_optional(&data.db_pool)
+        .await;
+
+    match token {
+        Ok(Some(share_token)) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Wishlist shared".to_string(),
+            data: ShareLink {
+                path: format!("/wishlists/shared/{}", share_token),
+                share_token,
+            },
+        }),
+        Ok(None) => wishlist_not_found(),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+pub async fn unshare_wishlist(
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    wishlist_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user_id = match Uuid::parse_str(&user.claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID format"),
+    };
+
+    let result = sqlx::query("UPDATE wishlists SET share_token = NULL WHERE id = $1 AND user_id = $2")
+        .bind(wishlist_id.into_inner())
+        .bind(user_id)
+        .execute(&data.db_pool)
+        .await;
+
+    match result {
+        Ok(done) if done.rows_affected() == 0 => wishlist_not_found(),
+        Ok(_) => HttpResponse::Ok().json(ApiResponse {
+            status: "Success".to_string(),
+            msg: "Share link revoked".to_string(),
+            data: "{}".to_string(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+// Public and read-only
+pub async fn get_shared_wishlist(
+    data: web::Data<AppState>,
+    token: web::Path<String>,
+    currency: RequestCurrency,
+    locale: RequestLocale,
+) -> impl Responder {
+    let wishlist = sqlx::query_as::<_, Wishlist>(&format!("{} WHERE w.share_token = $1", WISHLIST_SELECT))
+        .bind(token.as_str())
+        .fetch_optional(&data.db_pool)
+        .await;
+
+    let wishlist = match wishlist {
+        Ok(Some(wishlist)) => wishlist,
+        Ok(None) => return wishlist_not_found(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    };
+
+    match wishlist_view(&data, wishlist, currency.0, &locale.0).await {
+        Ok(mut view) => {
+            // The token is the owner's to hand out, not part of the shared view
+            view.wishlist.share_token = None;
+            HttpResponse::Ok().json(ApiResponse {
+                status: "Success".to_string(),
+                msg: "Shared wishlist".to_string(),
+                data: view,
+            })
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::product::models::ProductDetails;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Wishlist {
+    pub id: Uuid,
+    pub name: String,
+    pub share_token: Option<String>,
+    pub item_count: i64,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WishlistView {
+    #[serde(flatten)]
+    pub wishlist: Wishlist,
+    pub products: Vec<ProductDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewWishlist {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WishlistItem {
+    pub product_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveToCart {
+    pub quantity: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareLink {
+    pub share_token: String,
+    pub path: String,
+}